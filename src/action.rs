@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+
+/// Something the player can do on their turn.
+///
+/// Every change to a game goes through [`GameModel::apply`], so a list of
+/// actions plays a game back exactly: replays, remote players and bots all
+/// drive the model with the same values.
+///
+/// [`GameModel::apply`]: crate::game_model::GameModel::apply
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    /// Walks through the tunnel to the given room.
+    Move(usize),
+    /// Shoots an arrow along the given rooms.
+    Shoot(Vec<usize>),
+}
+
+impl Action {
+    /// The action that caused `event`, if it records one of the player's
+    /// actions.
+    pub fn from_event(event: &GameEvent) -> Option<Action> {
+        match event {
+            GameEvent::Moved { to, .. } => Some(Action::Move(*to)),
+            GameEvent::ArrowShot { path, .. } => Some(Action::Shoot(path.clone())),
+            _ => None,
+        }
+    }
+}
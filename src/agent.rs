@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::action::Action;
+use crate::game_model::GameModel;
+use crate::stats::Outcome;
+
+/// What the player perceives at the start of a turn: everything an
+/// automated player may use, and nothing it could not know.
+///
+/// It only carries what the player could sense, never where the wumpus,
+/// the pits or the bats are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Percept {
+    /// Number of rooms in the cave.
+    pub num_rooms: usize,
+    /// Room the player stands in.
+    pub room: usize,
+    /// Rooms the tunnels of the current room lead to.
+    pub exits: Vec<usize>,
+    /// The wumpus is in an adjacent room.
+    pub stench: bool,
+    /// A pit is in an adjacent room.
+    pub breeze: bool,
+    /// Bats are in an adjacent room.
+    pub squeak: bool,
+    /// The wumpus was killed; the game is won. Only ever set in the percept
+    /// passed to [`Agent::game_over`].
+    pub scream: bool,
+    /// The last action was refused and changed nothing.
+    pub bump: bool,
+    pub arrows: u32,
+}
+
+impl Percept {
+    /// What the player perceives in `model`; `bump` tells whether their
+    /// last action was refused.
+    pub fn observe(model: &GameModel, bump: bool) -> Self {
+        let room = model.player_position;
+        let clues = model.sense(room);
+        Percept {
+            num_rooms: model.cave.num_rooms(),
+            room,
+            exits: model.cave.exits(room).to_vec(),
+            stench: clues.stench,
+            breeze: clues.breeze,
+            squeak: clues.squeak,
+            scream: model.win,
+            bump,
+            arrows: model.arrows,
+        }
+    }
+}
+
+/// An automated player: picks its next action from what it perceives.
+///
+/// Agents are meant to see the game only through [`Percept`]s, as [`play`]
+/// drives them. The hidden state stays readable on [`GameModel`] itself,
+/// whose fields the front-ends draw from, so an agent handed the model
+/// instead could still peek at it.
+pub trait Agent {
+    fn act(&mut self, percept: &Percept) -> Action;
+
+    /// Called once with the last percept when the game is over, e.g. to hear
+    /// the scream of the wumpus.
+    fn game_over(&mut self, _percept: &Percept) {}
+}
+
+/// Lets `agent` play `model` until the game is over or `max_turns` actions
+/// have been tried, refused ones included. Returns how the game ended, or
+/// `None` if the agent ran out of turns.
+pub fn play(model: &mut GameModel, agent: &mut dyn Agent, max_turns: usize) -> Option<Outcome> {
+    let mut bump = false;
+    for _ in 0..max_turns {
+        if model.game_over {
+            break;
+        }
+        let action = agent.act(&Percept::observe(model, bump));
+        bump = model.apply(action).is_err();
+    }
+    if model.game_over {
+        agent.game_over(&Percept::observe(model, bump));
+    }
+    model.outcome()
+}
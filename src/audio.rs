@@ -0,0 +1,378 @@
+// Sound effects tied to the events of the game log. Each cue is read from
+// `assets/sfx/<name>.wav` when the file exists and is synthesized otherwise,
+// so the game never goes silent for a missing asset. Macroquad cannot pan a
+// sound, so every cue is prepared at a few fixed positions between the left
+// and the right speaker and the closest one is played.
+
+use std::f32::consts::{FRAC_PI_2, PI, SQRT_2, TAU};
+
+use macroquad::audio::{PlaySoundParams, Sound, load_sound_from_bytes, play_sound};
+use macroquad::file::load_file;
+use rustedbytes_wumpus::{GameEvent, GameModel};
+
+// Sample rate of the synthesized cues
+const SAMPLE_RATE: u32 = 22_050;
+
+// Positions each cue is prepared at, evenly spread from left to right
+const PAN_STEPS: usize = 5;
+
+// Horizontal distance on screen at which a sound comes from one side only
+const PAN_DISTANCE: f32 = 200.0;
+
+// Volume changes by this much at every press of F4
+pub const VOLUME_STEP: f32 = 0.25;
+
+// Loudness of the clues heard on entering a room, quieter than the events
+const CLUE_GAIN: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cue {
+    Footsteps,
+    Bow,
+    Roar,
+    Wind,
+    Bats,
+    Scream,
+    Fanfare,
+}
+
+impl Cue {
+    const ALL: [Cue; 7] = [
+        Cue::Footsteps,
+        Cue::Bow,
+        Cue::Roar,
+        Cue::Wind,
+        Cue::Bats,
+        Cue::Scream,
+        Cue::Fanfare,
+    ];
+
+    // Name of the asset file, without extension
+    fn name(self) -> &'static str {
+        match self {
+            Cue::Footsteps => "footsteps",
+            Cue::Bow => "bow",
+            Cue::Roar => "roar",
+            Cue::Wind => "wind",
+            Cue::Bats => "bats",
+            Cue::Scream => "scream",
+            Cue::Fanfare => "fanfare",
+        }
+    }
+
+    // Mono samples of the cue when no asset replaces it
+    fn synthesize(self) -> Vec<f32> {
+        let mut noise = Noise(0x2545_F491);
+        match self {
+            // Two muffled steps
+            Cue::Footsteps => samples(0.45, |t| {
+                let step = t % 0.25;
+                if step > 0.08 {
+                    return 0.0;
+                }
+                (noise.next() * 0.6 + (TAU * 90.0 * step).sin()) * (-step * 60.0).exp() * 0.7
+            }),
+            // A plucked string that drops in pitch
+            Cue::Bow => samples(0.4, |t| {
+                let frequency = 330.0 - 120.0 * t;
+                (TAU * frequency * t).sin() * (-t * 9.0).exp()
+            }),
+            // A low growl that swells and fades
+            Cue::Roar => samples(1.2, |t| {
+                let growl = ((70.0 * t) % 1.0) * 2.0 - 1.0;
+                let envelope = (PI * t / 1.2).sin();
+                (growl * 0.6 + noise.next() * 0.4) * envelope * (1.0 + (TAU * 6.0 * t).sin()) * 0.5
+            }),
+            // Soft noise rising and falling like a draught
+            Cue::Wind => {
+                let mut low = 0.0;
+                samples(1.5, |t| {
+                    low += (noise.next() - low) * 0.05;
+                    low * 3.0 * (PI * t / 1.5).sin()
+                })
+            }
+            // Quick bursts of flapping wings with a squeak
+            Cue::Bats => samples(0.8, |t| {
+                let flap = ((TAU * 11.0 * t).sin()).max(0.0).powi(3);
+                let squeak = if t % 0.4 < 0.05 {
+                    (TAU * 2_800.0 * t).sin() * 0.3
+                } else {
+                    0.0
+                };
+                noise.next() * flap * 0.7 + squeak
+            }),
+            // A falling wail
+            Cue::Scream => samples(1.2, |t| {
+                let frequency = 900.0 * (1.0 - t / 1.5) + 20.0 * (TAU * 7.0 * t).sin();
+                (TAU * frequency * t).sin() * (1.0 - t / 1.2) * 0.8
+            }),
+            // Four rising notes, the last one held
+            Cue::Fanfare => samples(1.3, |t| {
+                let note = ((t / 0.15) as usize).min(3);
+                let frequency = [523.25, 659.25, 783.99, 1_046.5][note];
+                let start = note as f32 * 0.15;
+                let tone = (TAU * frequency * t).sin() + 0.3 * (TAU * 2.0 * frequency * t).sin();
+                tone * (-(t - start) * 2.5).exp() * 0.6
+            }),
+        }
+    }
+}
+
+// Samples of `seconds` of sound computed from the time of each one
+fn samples(seconds: f32, mut wave: impl FnMut(f32) -> f32) -> Vec<f32> {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..count)
+        .map(|i| wave(i as f32 / SAMPLE_RATE as f32))
+        .collect()
+}
+
+// White noise from a xorshift generator, enough for sound effects
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+// Mono samples and sample rate of a 16-bit PCM WAV file, the only format
+// that can be panned; other files are played as they are
+fn decode_wav(bytes: &[u8]) -> Option<(Vec<f32>, u32)> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = bytes.get(offset + 8..offset + 8 + size)?;
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let word = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+                let rate = u32::from_le_bytes(body[4..8].try_into().ok()?);
+                format = Some((word(0), word(2) as usize, rate, word(14)));
+            }
+            b"data" => {
+                let (1, channels @ 1.., rate, 16) = format? else {
+                    return None;
+                };
+                let mono = body
+                    .chunks_exact(2 * channels)
+                    .map(|frame| {
+                        frame
+                            .chunks_exact(2)
+                            .map(|sample| i16::from_le_bytes([sample[0], sample[1]]) as f32)
+                            .sum::<f32>()
+                            / (channels as f32 * i16::MAX as f32)
+                    })
+                    .collect();
+                return Some((mono, rate));
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        offset += 8 + size + size % 2;
+    }
+    None
+}
+
+// A stereo 16-bit PCM WAV file of `mono` placed at `pan`, from -1 (left) to
+// 1 (right), keeping the same loudness wherever it is
+fn encode_wav(mono: &[f32], rate: u32, pan: f32) -> Vec<u8> {
+    let angle = (pan + 1.0) * FRAC_PI_2 / 2.0;
+    let gains = [angle.cos() * SQRT_2, angle.sin() * SQRT_2];
+    let data_size = (mono.len() * 4) as u32;
+
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&2u16.to_le_bytes()); // stereo
+    wav.extend_from_slice(&rate.to_le_bytes());
+    wav.extend_from_slice(&(rate * 4).to_le_bytes());
+    wav.extend_from_slice(&4u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in mono {
+        for gain in gains {
+            let value = (sample * gain).clamp(-1.0, 1.0) * i16::MAX as f32;
+            wav.extend_from_slice(&(value as i16).to_le_bytes());
+        }
+    }
+    wav
+}
+
+pub struct Audio {
+    // Sounds of each cue, from left to right; a single one if it cannot be
+    // panned, none if it could not be loaded at all
+    sounds: Vec<(Cue, Vec<Sound>)>,
+    pub volume: f32,
+    pub muted: bool,
+    // Entries of the game log already played
+    heard: usize,
+    // First entry of the log, to notice when a new game replaces the old one
+    opening: Option<GameEvent>,
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        Audio {
+            sounds: Vec::new(),
+            volume: 1.0,
+            muted: false,
+            heard: 0,
+            opening: None,
+        }
+    }
+
+    /// Prepares every cue, from its asset file if there is one.
+    pub async fn load(&mut self) {
+        for cue in Cue::ALL {
+            let path = format!("assets/sfx/{}.wav", cue.name());
+            let (mono, rate) = match load_file(&path).await {
+                Ok(bytes) => match decode_wav(&bytes) {
+                    Some(decoded) => decoded,
+                    None => {
+                        let sound = load_sound_from_bytes(&bytes).await.ok();
+                        if sound.is_none() {
+                            eprintln!("Effetto sonoro illeggibile: {}", path);
+                        }
+                        self.sounds.push((cue, sound.into_iter().collect()));
+                        continue;
+                    }
+                },
+                Err(_) => (cue.synthesize(), SAMPLE_RATE),
+            };
+
+            let mut variants = Vec::with_capacity(PAN_STEPS);
+            for step in 0..PAN_STEPS {
+                let pan = step as f32 / (PAN_STEPS - 1) as f32 * 2.0 - 1.0;
+                match load_sound_from_bytes(&encode_wav(&mono, rate, pan)).await {
+                    Ok(sound) => variants.push(sound),
+                    Err(_) => break,
+                }
+            }
+            // Without all the positions the cue is better left unpanned
+            if variants.len() < PAN_STEPS {
+                variants.truncate(1);
+            }
+            self.sounds.push((cue, variants));
+        }
+    }
+
+    /// Plays `cue` at `pan`, from -1 (left) to 1 (right), scaled by `gain`.
+    pub fn play(&self, cue: Cue, pan: f32, gain: f32) {
+        if self.muted {
+            return;
+        }
+        let Some((_, variants)) = self.sounds.iter().find(|(sound, _)| *sound == cue) else {
+            return;
+        };
+        let Some(last) = variants.len().checked_sub(1) else {
+            return;
+        };
+        let step = ((pan.clamp(-1.0, 1.0) + 1.0) / 2.0 * last as f32).round() as usize;
+        play_sound(
+            &variants[step],
+            PlaySoundParams {
+                looped: false,
+                volume: self.volume * gain,
+            },
+        );
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    /// Raises the volume by one step, starting over from the lowest one
+    /// after the loudest.
+    pub fn next_volume(&mut self) {
+        self.volume = if self.volume >= 1.0 {
+            VOLUME_STEP
+        } else {
+            (self.volume + VOLUME_STEP).min(1.0)
+        };
+    }
+
+    /// Volume as a percentage, for the player.
+    pub fn volume_percent(&self) -> u32 {
+        (self.volume * 100.0).round() as u32
+    }
+
+    /// Plays the cues of the events logged since the previous call.
+    pub fn update(&mut self, model: &GameModel) {
+        let log = model.log();
+        let opening = log.first().map(|entry| &entry.event);
+        if log.len() < self.heard || opening != self.opening.as_ref() {
+            self.heard = 0;
+            self.opening = opening.cloned();
+        }
+        if log.len() == self.heard {
+            return;
+        }
+
+        let pan = |room: usize| {
+            let from = model.room_positions[model.player_position];
+            (model.room_positions[room].x - from.x) / PAN_DISTANCE
+        };
+        let mut arrived = false;
+        for entry in &log[self.heard..] {
+            match &entry.event {
+                GameEvent::GameStarted { .. } => arrived = true,
+                GameEvent::Moved { .. } => {
+                    self.play(Cue::Footsteps, 0.0, 1.0);
+                    arrived = true;
+                }
+                GameEvent::CarriedByBats { .. } => {
+                    self.play(Cue::Bats, 0.0, 1.0);
+                    arrived = true;
+                }
+                GameEvent::ArrowShot { flight, .. } => {
+                    let pan = flight.first().map_or(0.0, |&room| pan(room));
+                    self.play(Cue::Bow, pan, 1.0);
+                }
+                // Where the wumpus went is hidden, so its roar comes from
+                // nowhere in particular
+                GameEvent::WumpusWoke { .. } => self.play(Cue::Roar, 0.0, 1.0),
+                GameEvent::WumpusWandered { .. } => self.play(Cue::Roar, 0.0, CLUE_GAIN),
+                GameEvent::EatenByWumpus { .. } => self.play(Cue::Roar, 0.0, 1.0),
+                GameEvent::FellInPit { .. } | GameEvent::ShotSelf { .. } => {
+                    self.play(Cue::Scream, 0.0, 1.0);
+                }
+                GameEvent::WumpusKilled { .. } => self.play(Cue::Fanfare, 0.0, 1.0),
+                _ => {}
+            }
+        }
+        self.heard = log.len();
+
+        // The hazards next to the room reached can be heard from where they are
+        if arrived && !model.game_over {
+            let sources = model.clue_sources(model.player_position);
+            for room in sources.stench {
+                self.play(Cue::Roar, pan(room), CLUE_GAIN / 2.0);
+            }
+            for room in sources.breeze {
+                self.play(Cue::Wind, pan(room), CLUE_GAIN);
+            }
+            for room in sources.squeak {
+                self.play(Cue::Bats, pan(room), CLUE_GAIN);
+            }
+        }
+    }
+
+    /// Marks the whole log as heard, e.g. for a game loaded from a save
+    /// whose past should not be replayed aloud.
+    pub fn skip(&mut self, model: &GameModel) {
+        let log = model.log();
+        self.heard = log.len();
+        self.opening = log.first().map(|entry| entry.event.clone());
+    }
+}
@@ -0,0 +1,209 @@
+// Hunt the Wumpus nel terminale, senza bisogno di una finestra.
+// Di default è un interprete di comandi riga per riga come l'originale del
+// 1973; con --tui diventa un'interfaccia a tutto schermo con la mappa.
+// Con --daily si gioca la caverna del giorno, con --verify "<risultato>" si
+// controlla un risultato condiviso, con --practice si gioca in allenamento.
+// Con --bot <partite> gioca il risolutore, per misurarne la bravura.
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rustedbytes_wumpus::{
+    DailyHistory, Date, Difficulty, GameModel, Language, Message, Outcome, Profile, Settings,
+    Solver, SystemClock, Translations, agent, daily,
+};
+
+mod repl;
+mod tui;
+
+use repl::TextGame;
+
+/// Value of a `--name <value>` or `--name=<value>` command line argument.
+fn find_arg(name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Directory for the game's files, shared with the graphical version.
+fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustedbytes-wumpus")
+}
+
+/// Settings of the graphical version, whose language and game options the
+/// text version follows too.
+fn load_settings() -> Settings {
+    let path = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustedbytes-wumpus")
+        .join("settings.toml");
+    Settings::load(&path).unwrap_or_else(|err| {
+        eprintln!("Impostazioni illeggibili: {}", err);
+        Settings::default()
+    })
+}
+
+/// Adds a finished game to the player's statistics, the same ones the
+/// graphical version keeps. For a daily cave it also stores the result,
+/// and returns it to be shared.
+fn record_game(model: &GameModel, daily: Option<Date>) -> Option<String> {
+    let path = data_dir().join("profile.json");
+    let result = Profile::load(&path).and_then(|mut profile| {
+        profile.record(model);
+        profile.save(&path)
+    });
+    if let Err(err) = result {
+        eprintln!("Statistiche del giocatore non aggiornate: {}", err);
+    }
+
+    let date = daily?;
+    let result = daily::share(model, date)?;
+    let saved = DailyHistory::load(&daily_path()).and_then(|mut history| {
+        history.finish(date, result.clone());
+        history.save(&daily_path())
+    });
+    if let Err(err) = saved {
+        eprintln!("Caverne del giorno non salvate: {}", err);
+    }
+    Some(result)
+}
+
+// Actions after which a game the solver has not finished is given up
+const BOT_TURNS: usize = 1000;
+
+/// Lets the solver play `games` games in a row, starting from `model`, and
+/// tells how well it did. Nothing is recorded in the player's statistics.
+fn run_bot(mut model: GameModel, games: u32, translations: &Translations) {
+    let mut wins = 0;
+    let mut moves = 0;
+    for _ in 0..games {
+        let mut solver = Solver::new(model.difficulty);
+        if agent::play(&mut model, &mut solver, BOT_TURNS) == Some(Outcome::Won) {
+            wins += 1;
+            moves += model.moves_count;
+        }
+        model.reset();
+    }
+    let message = Message::new("bot.result")
+        .arg("wins", wins)
+        .arg("games", games)
+        .arg(
+            "percent",
+            format!("{:.1}", 100.0 * wins as f64 / games.max(1) as f64),
+        )
+        .arg("moves", format!("{:.1}", moves as f64 / wins.max(1) as f64))
+        .arg("difficulty", translations.get(model.difficulty.key()));
+    println!("{}", translations.format(&message));
+}
+
+/// Location of the daily caves already played, shared with the graphical
+/// version.
+fn daily_path() -> PathBuf {
+    data_dir().join("daily.json")
+}
+
+/// Location of the high scores, shared with the graphical version.
+fn leaderboard_path() -> PathBuf {
+    data_dir().join("leaderboard.json")
+}
+
+fn main() -> io::Result<()> {
+    let settings = load_settings();
+    let language = find_arg("--lang")
+        .and_then(|code| Language::from_code(&code))
+        .or(settings.language)
+        .or_else(Language::from_env)
+        .unwrap_or(Language::FALLBACK);
+    let dir = data_dir().join("lang");
+    let translations = Translations::with_overrides(language, &dir)
+        .unwrap_or_else(|_| Translations::new(language));
+
+    if let Some(result) = find_arg("--verify") {
+        let message = match daily::verify(&result) {
+            Ok((date, outcome)) => Message::new("daily.verified")
+                .arg("date", date)
+                .arg("outcome", outcome.key()),
+            Err(err) => err.message(),
+        };
+        println!("{}", translations.format(&message));
+        return Ok(());
+    }
+
+    let mut model = match find_arg("--seed") {
+        Some(value) => match value.parse() {
+            Ok(seed) => GameModel::from_seed(seed),
+            Err(_) => {
+                eprintln!("Seme non valido: {}", value);
+                GameModel::new()
+            }
+        },
+        None => GameModel::new(),
+    };
+    model.set_cave_kind(settings.cave);
+    model.set_difficulty(settings.difficulty);
+
+    if let Some(name) = find_arg("--difficulty") {
+        match Difficulty::from_name(&name) {
+            Some(difficulty) => model.set_difficulty(difficulty),
+            None => eprintln!("Difficoltà sconosciuta: {}", name),
+        }
+    }
+
+    // In allenamento si possono annullare le azioni, ma la partita non conta
+    if settings.practice || std::env::args().any(|arg| arg == "--practice") {
+        model.set_practice(true);
+    }
+
+    if let Some(value) = find_arg("--bot") {
+        match value.parse() {
+            Ok(games) => run_bot(model, games, &translations),
+            Err(_) => eprintln!("Numero di partite non valido: {}", value),
+        }
+        return Ok(());
+    }
+
+    // La caverna del giorno si gioca una volta sola: il tentativo conta
+    // appena la partita comincia
+    let mut daily = None;
+    if std::env::args().any(|arg| arg == "--daily") {
+        let date = Date::today();
+        let mut history = DailyHistory::load(&daily_path())?;
+        if !history.start(date) {
+            println!(
+                "{}",
+                translations.format(&Message::new("text.daily_done").arg("date", date))
+            );
+            if let Some(result) = history.result(date) {
+                println!(
+                    "{}",
+                    translations.format(&Message::new("text.share").arg("result", result))
+                );
+            }
+            return Ok(());
+        }
+        history.save(&daily_path())?;
+        model = daily::daily_game(date, Arc::new(SystemClock::new()));
+        model.set_message(Message::new("daily.started").arg("date", date));
+        daily = Some(date);
+    }
+
+    if std::env::args().any(|arg| arg == "--tui") {
+        return tui::run(model, translations, daily);
+    }
+
+    let stdin = io::stdin();
+    TextGame::new(model, translations, stdin.lock(), io::stdout().lock())
+        .daily(daily)
+        .run()
+}
@@ -0,0 +1,343 @@
+// Interprete di comandi riga per riga, come l'originale del 1973.
+// Legge dallo standard input, quindi funziona anche via SSH o con l'input
+// rediretto da un file.
+
+use std::io::{self, BufRead, Write};
+
+use rustedbytes_wumpus::{
+    Action, Date, GameModel, HighScore, Leaderboard, Message, Solver, Translations, leaderboard,
+};
+
+/// A line typed by the player.
+enum Command {
+    Move(usize),
+    Shoot(Vec<usize>),
+    Undo,
+    Redo,
+    Hint,
+    Help,
+    Quit,
+}
+
+/// Drives a game through a line-oriented command interpreter.
+pub struct TextGame<R, W> {
+    model: GameModel,
+    translations: Translations,
+    input: R,
+    output: W,
+    // Date of the daily cave, while it is the game being played
+    daily: Option<Date>,
+}
+
+impl<R: BufRead, W: Write> TextGame<R, W> {
+    pub fn new(model: GameModel, translations: Translations, input: R, output: W) -> Self {
+        TextGame {
+            model,
+            translations,
+            input,
+            output,
+            daily: None,
+        }
+    }
+
+    /// Marks the first game as the daily cave of `date`, whose result is
+    /// kept and offered for sharing.
+    pub fn daily(mut self, date: Option<Date>) -> Self {
+        self.daily = date;
+        self
+    }
+
+    /// Plays games until the player quits or the input ends.
+    pub fn run(&mut self) -> io::Result<()> {
+        writeln!(self.output, "HUNT THE WUMPUS")?;
+        self.say(&Message::new("text.help"))?;
+        self.say(
+            &Message::new("hud.difficulty")
+                .arg("difficulty", self.model.difficulty.key())
+                .arg("bats", self.model.difficulty.rules().bats_key()),
+        )?;
+
+        if let Some(date) = self.daily {
+            self.say(&Message::new("daily.started").arg("date", date))?;
+        }
+        if self.model.is_practice() {
+            self.say(&Message::new("text.practice"))?;
+        }
+
+        self.model.generate_warnings();
+        loop {
+            writeln!(self.output)?;
+            self.describe_room()?;
+
+            while !self.model.game_over {
+                let Some(line) = self.prompt(&Message::new("text.prompt"))? else {
+                    return Ok(());
+                };
+                match parse_command(&line) {
+                    Ok(Command::Move(room)) => match self.model.apply(Action::Move(room)) {
+                        Ok(_) => self.describe_room()?,
+                        Err(err) => self.say(&err.message())?,
+                    },
+                    Ok(Command::Shoot(path)) => match self.model.apply(Action::Shoot(path)) {
+                        Ok(_) => self.report()?,
+                        Err(err) => self.say(&err.message())?,
+                    },
+                    Ok(Command::Undo) => match self.model.undo() {
+                        Ok(()) => self.describe_room()?,
+                        Err(err) => self.say(&err.message())?,
+                    },
+                    Ok(Command::Redo) => match self.model.redo() {
+                        Ok(()) => self.describe_room()?,
+                        Err(err) => self.say(&err.message())?,
+                    },
+                    Ok(Command::Hint) if !self.model.is_practice() => {
+                        self.say(&Message::new("hint.practice_only"))?
+                    }
+                    Ok(Command::Hint) => {
+                        if let Some(decision) = Solver::hint(&self.model) {
+                            self.say(&decision.message())?;
+                        }
+                    }
+                    Ok(Command::Help) => self.say(&Message::new("text.help"))?,
+                    Ok(Command::Quit) => return self.say(&Message::new("text.goodbye")),
+                    Err(message) => self.say(&message)?,
+                }
+            }
+
+            let shared = crate::record_game(&self.model, self.daily.take());
+            let stats = self.model.stats();
+            self.say(&stats.message())?;
+            self.say(
+                &Message::new("over.time")
+                    .arg("time", format!("{:.1}", stats.time))
+                    .arg("seed", self.model.seed()),
+            )?;
+            if let Some(score) = leaderboard::score(&self.model) {
+                self.say(&Message::new("text.score").arg("score", score))?;
+                if self.enter_high_score(score).is_err() {
+                    self.say(&Message::new("leaderboard.save_failed"))?;
+                }
+            }
+            if let Some(result) = shared {
+                self.say(&Message::new("text.share").arg("result", result))?;
+            }
+
+            // In allenamento si può anche annullare l'azione che ha chiuso
+            // la partita e continuare da lì
+            let question = if self.model.is_practice() {
+                "text.play_again_practice"
+            } else {
+                "text.play_again"
+            };
+            let answer = self.prompt(&Message::new(question))?;
+            if matches!(
+                answer.as_deref().map(parse_command),
+                Some(Ok(Command::Undo))
+            ) && self.model.undo().is_ok()
+            {
+                continue;
+            }
+            if !answer.is_some_and(|answer| self.is_yes(&answer)) {
+                return self.say(&Message::new("text.goodbye"));
+            }
+            self.model.reset();
+            self.model.generate_warnings();
+        }
+    }
+
+    // Feedback of the last action, then where the player stands now
+    fn describe_room(&mut self) -> io::Result<()> {
+        self.report()?;
+        if self.model.game_over {
+            return Ok(());
+        }
+
+        let exits = self
+            .model
+            .cave
+            .exits(self.model.player_position)
+            .iter()
+            .map(|room| room.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.say(&Message::new("text.exits").arg("exits", exits))?;
+        self.say(&Message::new("text.arrows").arg("arrows", self.model.arrows))
+    }
+
+    // Whether the answer starts with one of the letters that mean yes in
+    // the current language
+    fn is_yes(&self, answer: &str) -> bool {
+        let letters = self.translations.get("text.yes");
+        answer
+            .chars()
+            .next()
+            .is_some_and(|c| letters.contains(c.to_ascii_uppercase()))
+    }
+
+    fn report(&mut self) -> io::Result<()> {
+        let text = self.translations.format_all(&self.model.message);
+        writeln!(self.output, "{}", text)
+    }
+
+    // Asks the name of a player who made the leaderboard and adds them
+    fn enter_high_score(&mut self, score: u32) -> io::Result<()> {
+        let path = crate::leaderboard_path();
+        let mut board = Leaderboard::load(&path)?;
+        if !board.qualifies(self.model.difficulty, self.model.cave.kind(), score) {
+            return Ok(());
+        }
+
+        let name = match self.prompt(&Message::new("text.name"))? {
+            Some(name) if !name.is_empty() => name,
+            _ => return Ok(()),
+        };
+        let Some(rank) = HighScore::new(&self.model, &name).and_then(|entry| board.insert(entry))
+        else {
+            return Ok(());
+        };
+        board.save(&path)?;
+        self.say(&Message::new("text.ranked").arg("rank", rank + 1))
+    }
+
+    fn say(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.output, "{}", self.translations.format(message))
+    }
+
+    /// Asks a question and reads the answer; None when the input is over.
+    fn prompt(&mut self, question: &Message) -> io::Result<Option<String>> {
+        write!(self.output, "{} ", self.translations.format(question))?;
+        self.output.flush()?;
+
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            return Ok(None);
+        }
+        Ok(Some(line.trim().to_string()))
+    }
+}
+
+/// Parses commands such as `MOVE 4`, `m 4`, `SHOOT 4 5 6` or `QUIT`.
+/// Errors come back as the message to show to the player.
+fn parse_command(line: &str) -> Result<Command, Message> {
+    let mut words = line.split_whitespace();
+    let verb = words.next().unwrap_or("").to_ascii_uppercase();
+    let rooms = words
+        .map(|word| {
+            word.parse::<usize>()
+                .map_err(|_| Message::new("text.not_a_room").arg("value", word))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match verb.as_str() {
+        "M" | "MOVE" => match rooms.as_slice() {
+            [room] => Ok(Command::Move(*room)),
+            _ => Err(Message::new("text.missing_room")),
+        },
+        "S" | "SHOOT" if rooms.is_empty() => Err(Message::new("text.missing_room")),
+        "S" | "SHOOT" => Ok(Command::Shoot(rooms)),
+        "U" | "UNDO" if rooms.is_empty() => Ok(Command::Undo),
+        "REDO" if rooms.is_empty() => Ok(Command::Redo),
+        "HINT" if rooms.is_empty() => Ok(Command::Hint),
+        "H" | "HELP" | "?" => Ok(Command::Help),
+        "Q" | "QUIT" => Ok(Command::Quit),
+        _ => Err(Message::new("text.unknown_command").arg("command", line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustedbytes_wumpus::{Language, ManualClock};
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn error_key(line: &str) -> String {
+        match parse_command(line) {
+            Err(message) => message.key,
+            Ok(_) => panic!("{:?} should not parse", line),
+        }
+    }
+
+    #[test]
+    fn commands_parse_in_full_and_by_initial() {
+        for line in ["MOVE 4", "move 4", "M 4", "  m   4  "] {
+            assert!(
+                matches!(parse_command(line), Ok(Command::Move(4))),
+                "{}",
+                line
+            );
+        }
+        for line in ["SHOOT 4 5 6", "s 4 5 6"] {
+            assert!(
+                matches!(parse_command(line), Ok(Command::Shoot(path)) if path == [4, 5, 6]),
+                "{}",
+                line
+            );
+        }
+        assert!(matches!(parse_command("U"), Ok(Command::Undo)));
+        assert!(matches!(parse_command("undo"), Ok(Command::Undo)));
+        assert!(matches!(parse_command("REDO"), Ok(Command::Redo)));
+        assert!(matches!(parse_command("hint"), Ok(Command::Hint)));
+        for line in ["H", "help", "?"] {
+            assert!(matches!(parse_command(line), Ok(Command::Help)), "{}", line);
+        }
+        assert!(matches!(parse_command("Q"), Ok(Command::Quit)));
+        assert!(matches!(parse_command("quit"), Ok(Command::Quit)));
+    }
+
+    #[test]
+    fn bad_commands_say_what_is_wrong() {
+        assert_eq!(error_key("M"), "text.missing_room");
+        assert_eq!(error_key("M 4 5"), "text.missing_room");
+        assert_eq!(error_key("SHOOT"), "text.missing_room");
+        assert_eq!(error_key("M four"), "text.not_a_room");
+        assert_eq!(error_key("UNDO 3"), "text.unknown_command");
+        assert_eq!(error_key("DANCE"), "text.unknown_command");
+        assert_eq!(error_key(""), "text.unknown_command");
+    }
+
+    #[test]
+    fn scripted_session() {
+        let model = GameModel::with_seed_and_clock(3, Arc::new(ManualClock::new(0.0)));
+        let start = model.player_position;
+        let exits = model.cave.exits(start).to_vec();
+        let far = (0..model.cave.num_rooms())
+            .find(|room| *room != start && !exits.contains(room))
+            .unwrap();
+        // An exit the player survives, found on a copy of the game
+        let safe = *exits
+            .iter()
+            .find(|&&exit| {
+                let mut copy = model.clone();
+                copy.apply(Action::Move(exit)).is_ok()
+                    && !copy.game_over
+                    && copy.player_position == exit
+            })
+            .unwrap();
+        let script = format!("DANCE\nM {}\nm {}\nQUIT\n", far, safe);
+        let cave = model.cave.clone();
+
+        let translations = Translations::new(Language::English);
+        let mut output = Vec::new();
+        TextGame::new(model, translations, Cursor::new(script), &mut output)
+            .run()
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        // The room and exits at the start, then after the move
+        let tunnels = |room: usize| {
+            let exits = cave.exits(room).iter().map(|r| r.to_string());
+            format!("Tunnels lead to {}.", exits.collect::<Vec<_>>().join(" "))
+        };
+        let before = output.find(&tunnels(start)).unwrap();
+        let after = output.find(&tunnels(safe)).unwrap();
+        assert!(output.starts_with("HUNT THE WUMPUS\n"));
+        assert!(output[..before].contains(&format!("You are in room {}.", start)));
+        assert!(output[before..after].contains("I don't understand \"DANCE\""));
+        assert!(
+            output[before..after].contains(&format!("You cannot go straight to room {}!", far))
+        );
+        assert!(output.ends_with("Bye!\n"));
+    }
+}
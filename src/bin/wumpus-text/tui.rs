@@ -0,0 +1,547 @@
+// Interfaccia a tutto schermo nel terminale: la mappa della caverna in
+// caratteri ASCII, la riga di stato e il registro degli eventi.
+// Tasti e mouse fanno le stesse azioni della versione grafica.
+
+use std::io::{self, Stdout, Write};
+
+use crossterm::{
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    execute, queue,
+    style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use rustedbytes_wumpus::{
+    Date, GameModel, Marker, Message, PlayCommand, PlayControls, Translations,
+};
+
+// Rows below the map: separator, status, message, log and help
+const LOG_LINES: usize = 4;
+const PANEL_HEIGHT: usize = LOG_LINES + 4;
+
+// Rooms are drawn as labels such as "[12]", centered on their position
+const LABEL_WIDTH: i32 = 4;
+
+/// Plays in the terminal until the player quits; `daily` is the date of
+/// the daily cave being played, if it is one.
+pub fn run(
+    model: GameModel,
+    translations: Translations,
+    mut daily: Option<Date>,
+) -> io::Result<()> {
+    let mut screen = Screen::enter()?;
+    let mut tui = Tui {
+        model,
+        translations,
+        controls: PlayControls::new(),
+        fog_of_war: true,
+        selected_room: None,
+        labels: Vec::new(),
+    };
+
+    loop {
+        let (width, height) = terminal::size()?;
+        let canvas = tui.draw(width as usize, height as usize);
+        canvas.flush(&mut screen.out)?;
+
+        let was_over = tui.model.game_over;
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press && !tui.handle_key(key) => {
+                return Ok(());
+            }
+            Event::Mouse(mouse) => tui.handle_mouse(mouse),
+            _ => {}
+        }
+        if tui.model.game_over && !was_over {
+            let daily = daily.take();
+            if let Some(result) = crate::record_game(&tui.model, daily) {
+                tui.model
+                    .message
+                    .push(Message::new("text.share").arg("result", result));
+            }
+        }
+    }
+}
+
+/// Raw mode on the alternate screen, restored when dropped, also on panic.
+struct Screen {
+    out: Stdout,
+}
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut out = io::stdout();
+        execute!(out, EnterAlternateScreen, EnableMouseCapture, cursor::Hide)?;
+        Ok(Screen { out })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(
+            self.out,
+            cursor::Show,
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Style {
+    color: Color,
+    bold: bool,
+}
+
+impl Style {
+    const fn plain(color: Color) -> Self {
+        Style { color, bold: false }
+    }
+
+    const fn bold(color: Color) -> Self {
+        Style { color, bold: true }
+    }
+}
+
+/// A grid of characters drawn off screen, then printed in one go.
+struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<(char, Style)>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            cells: vec![(' ', Style::plain(Color::Reset)); width * height],
+        }
+    }
+
+    fn put(&mut self, x: i32, y: i32, ch: char, style: Style) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.cells[y as usize * self.width + x as usize] = (ch, style);
+        }
+    }
+
+    fn is_empty(&self, x: i32, y: i32) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && self.cells[y as usize * self.width + x as usize].0 == ' '
+    }
+
+    fn text(&mut self, x: i32, y: i32, text: &str, style: Style) {
+        for (i, ch) in text.chars().enumerate() {
+            self.put(x + i as i32, y, ch, style);
+        }
+    }
+
+    /// Straight line of slope characters that only fills empty cells, with
+    /// an arrow head at the end for one-way tunnels.
+    fn tunnel(&mut self, from: (i32, i32), to: (i32, i32), directed: bool, style: Style) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        // Cells are about twice as tall as they are wide
+        let (run, rise) = (dx.abs() as f32, (dy.abs() * 2) as f32);
+        let ch = if rise < run * 0.4 {
+            '-'
+        } else if run < rise * 0.4 {
+            '|'
+        } else if (dx > 0) == (dy > 0) {
+            '\\'
+        } else {
+            '/'
+        };
+
+        let steps = dx.abs().max(dy.abs());
+        let point = |i: i32| {
+            (
+                from.0 + (dx as f32 * i as f32 / steps as f32).round() as i32,
+                from.1 + (dy as f32 * i as f32 / steps as f32).round() as i32,
+            )
+        };
+        for i in 1..steps {
+            let (x, y) = point(i);
+            if self.is_empty(x, y) {
+                self.put(x, y, ch, style);
+            }
+        }
+
+        if directed && steps > 2 {
+            // Just outside the label of the destination room
+            let head = if run >= rise {
+                if dx > 0 { '>' } else { '<' }
+            } else if dy > 0 {
+                'v'
+            } else {
+                '^'
+            };
+            let back = (1..steps)
+                .rev()
+                .map(point)
+                .find(|&(x, y)| y != to.1 || (x - to.0).abs() > LABEL_WIDTH / 2);
+            if let Some((x, y)) = back {
+                self.put(x, y, head, style);
+            }
+        }
+    }
+
+    fn flush(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut current = None;
+        for y in 0..self.height {
+            queue!(out, cursor::MoveTo(0, y as u16))?;
+            for &(ch, style) in &self.cells[y * self.width..(y + 1) * self.width] {
+                if current != Some(style) {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Reset),
+                        SetForegroundColor(style.color)
+                    )?;
+                    if style.bold {
+                        queue!(out, SetAttribute(Attribute::Bold))?;
+                    }
+                    current = Some(style);
+                }
+                queue!(out, Print(ch))?;
+            }
+        }
+        queue!(out, SetAttribute(Attribute::Reset))?;
+        out.flush()
+    }
+}
+
+struct Tui {
+    model: GameModel,
+    translations: Translations,
+    controls: PlayControls,
+    fog_of_war: bool,
+    // Room under the mouse pointer
+    selected_room: Option<usize>,
+    // Where each room label was drawn: room, first column, row
+    labels: Vec<(usize, i32, i32)>,
+}
+
+impl Tui {
+    fn tr(&self, key: &'static str) -> &str {
+        self.translations.get(key)
+    }
+
+    fn text(&self, message: &Message) -> String {
+        self.translations.format(message)
+    }
+
+    /// Handles a key press; false when the player quits.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let command = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char('q') | KeyCode::Char('Q') => return false,
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                self.fog_of_war = !self.fog_of_war;
+                return true;
+            }
+            KeyCode::Char('r') | KeyCode::Char('R') if self.model.game_over => {
+                self.model.reset();
+                self.controls.reset();
+                return true;
+            }
+            KeyCode::Char(' ') => PlayCommand::ToggleShootMode,
+            KeyCode::Esc => PlayCommand::Cancel,
+            KeyCode::Left | KeyCode::Up | KeyCode::BackTab => PlayCommand::FocusPrevious,
+            KeyCode::Right | KeyCode::Down | KeyCode::Tab => PlayCommand::FocusNext,
+            KeyCode::Char(digit @ '1'..='9') => {
+                PlayCommand::ChooseExit(digit as usize - '1' as usize)
+            }
+            KeyCode::Enter => PlayCommand::Confirm,
+            KeyCode::Backspace => PlayCommand::RemoveLastStep,
+            KeyCode::Char('z') | KeyCode::Char('Z') => PlayCommand::Undo,
+            KeyCode::Char('y') | KeyCode::Char('Y') => PlayCommand::Redo,
+            KeyCode::Char('h') | KeyCode::Char('H') => PlayCommand::Hint,
+            KeyCode::Char('m') | KeyCode::Char('M') => match self.selected_room {
+                // A marker goes on the room under the pointer, if any
+                Some(room) => {
+                    self.model.map.cycle_marker(room);
+                    return true;
+                }
+                None => PlayCommand::CycleMarker,
+            },
+            _ => return true,
+        };
+
+        // In allenamento si può annullare anche l'azione che ha chiuso la partita
+        if !self.model.game_over || matches!(command, PlayCommand::Undo | PlayCommand::Redo) {
+            self.controls.execute(&mut self.model, command);
+        }
+        true
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let room = self.room_at(mouse.column as i32, mouse.row as i32);
+        match mouse.kind {
+            MouseEventKind::Moved => self.selected_room = room,
+            _ if self.model.game_over => {}
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(room) = room {
+                    self.controls
+                        .execute(&mut self.model, PlayCommand::ChooseRoom(room));
+                }
+            }
+            // The right button fires the arrow, or marks a room while moving
+            MouseEventKind::Down(MouseButton::Right) => {
+                if self.controls.shoot_mode {
+                    self.controls.execute(&mut self.model, PlayCommand::Fire);
+                } else if let Some(room) = room {
+                    self.model.map.cycle_marker(room);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn room_at(&self, column: i32, row: i32) -> Option<usize> {
+        self.labels
+            .iter()
+            .find(|&&(_, x, y)| row == y && (x..x + LABEL_WIDTH).contains(&column))
+            .map(|&(room, _, _)| room)
+    }
+
+    fn draw(&mut self, width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        let map_height = height.saturating_sub(PANEL_HEIGHT);
+        self.draw_map(&mut canvas, width, map_height);
+        self.draw_panel(&mut canvas, map_height);
+        canvas
+    }
+
+    // Character cell of every room, scaling the map to the area above the
+    // panel
+    fn room_cells(&self, width: usize, height: usize) -> Vec<(i32, i32)> {
+        let positions = &self.model.room_positions;
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for pos in positions {
+            min_x = min_x.min(pos.x);
+            min_y = min_y.min(pos.y);
+            max_x = max_x.max(pos.x);
+            max_y = max_y.max(pos.y);
+        }
+
+        // Room for the labels and a clue line under the lowest rooms
+        let left = LABEL_WIDTH as f32 / 2.0 + 1.0;
+        let columns = (width as f32 - LABEL_WIDTH as f32 - 2.0).max(1.0);
+        let rows = (height as f32 - 3.0).max(1.0);
+        positions
+            .iter()
+            .map(|pos| {
+                let x = (pos.x - min_x) / (max_x - min_x).max(1.0);
+                let y = (pos.y - min_y) / (max_y - min_y).max(1.0);
+                (
+                    (left + x * columns).round() as i32,
+                    (1.0 + y * rows).round() as i32,
+                )
+            })
+            .collect()
+    }
+
+    fn draw_map(&mut self, canvas: &mut Canvas, width: usize, height: usize) {
+        let model = &self.model;
+        let cells = self.room_cells(width, height);
+        let fog = self.fog_of_war && !model.game_over;
+        let known = model.known_rooms();
+        let exits = if model.game_over {
+            &[][..]
+        } else {
+            self.controls.exits(model)
+        };
+        let origin = self.controls.exits_origin(model);
+        let focused = self.controls.focused_room(model);
+
+        for (from, to) in model.cave.edges() {
+            let visible = model.map.is_visited(from)
+                || (!model.cave.is_directed() && model.map.is_visited(to));
+            if fog && !visible {
+                continue;
+            }
+            let style = if !model.game_over
+                && ((from == origin && exits.contains(&to))
+                    || (to == origin && exits.contains(&from) && !model.cave.is_directed()))
+            {
+                Style::plain(Color::Yellow)
+            } else {
+                Style::plain(Color::DarkGrey)
+            };
+            canvas.tunnel(cells[from], cells[to], model.cave.is_directed(), style);
+        }
+
+        self.labels.clear();
+        for (room, &(x, y)) in cells.iter().enumerate() {
+            if fog && !known[room] {
+                continue;
+            }
+
+            let visited = model.map.is_visited(room);
+            let style = if room == model.player_position {
+                Style::bold(Color::Green)
+            } else if (model.game_over && room == model.wumpus_position)
+                || self.controls.arrow_path.contains(&room)
+            {
+                Style::bold(Color::Red)
+            } else if focused == Some(room) && !model.game_over {
+                Style::bold(Color::Cyan)
+            } else if exits.contains(&room) {
+                Style::plain(Color::Yellow)
+            } else if visited || !fog {
+                Style::plain(Color::White)
+            } else {
+                Style::plain(Color::DarkGrey)
+            };
+            let label = if visited || !fog {
+                format!("[{:>2}]", room)
+            } else {
+                format!("({:>2})", room)
+            };
+            let left = x - LABEL_WIDTH / 2;
+            canvas.text(left, y, &label, style);
+            self.labels.push((room, left, y));
+
+            if let Some(i) = exits.iter().position(|&exit| exit == room) {
+                canvas.text(
+                    left + LABEL_WIDTH,
+                    y,
+                    &(i + 1).to_string(),
+                    Style::plain(Color::Yellow),
+                );
+            }
+
+            self.draw_room_notes(canvas, room, left, y + 1);
+        }
+    }
+
+    // Clue letters under visited rooms, markers under the others
+    fn draw_room_notes(&self, canvas: &mut Canvas, room: usize, x: i32, y: i32) {
+        if let Some(clues) = self.model.map.clues(room) {
+            let notes = [
+                (clues.stench, "tui.stench", Color::DarkYellow),
+                (clues.breeze, "tui.breeze", Color::Cyan),
+                (clues.squeak, "tui.squeak", Color::Magenta),
+            ];
+            let sensed = notes.iter().filter(|(sensed, _, _)| *sensed);
+            for (x, (_, key, color)) in (x..).zip(sensed) {
+                canvas.text(x, y, self.tr(key), Style::plain(*color));
+            }
+            return;
+        }
+
+        let label = self
+            .model
+            .map
+            .markers(room)
+            .iter()
+            .map(|marker| {
+                self.tr(match marker {
+                    Marker::Pit => "marker.pit",
+                    Marker::Bats => "marker.bats",
+                    Marker::Wumpus => "marker.wumpus",
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        canvas.text(x, y, &label, Style::plain(Color::Red));
+    }
+
+    fn draw_panel(&self, canvas: &mut Canvas, top: usize) {
+        let model = &self.model;
+        let top = top as i32;
+        let width = canvas.width;
+        canvas.text(0, top, &"-".repeat(width), Style::plain(Color::DarkGrey));
+
+        // Status: arrows, clue legend and seed, or the outcome of the game
+        let quiver = format!(
+            "{}{}",
+            "x".repeat(model.arrows as usize),
+            " ".repeat(model.difficulty.rules().arrows.saturating_sub(model.arrows) as usize)
+        );
+        let mut status = self.text(&Message::new("hud.arrows").arg("arrows", quiver));
+        status += "   ";
+        status += &self.text(
+            &Message::new("hud.difficulty")
+                .arg("difficulty", model.difficulty.key())
+                .arg("bats", model.difficulty.rules().bats_key()),
+        );
+        if model.is_practice() {
+            status += "   ";
+            status += self.tr("hud.practice");
+        }
+        if self.controls.shoot_mode {
+            let rooms = self
+                .controls
+                .arrow_path
+                .iter()
+                .map(|room| room.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            status += "   ";
+            status += &self.text(&Message::new("hud.arrow_path").arg("rooms", rooms));
+        }
+        canvas.text(1, top + 1, &status, Style::plain(Color::Cyan));
+
+        let legend = [
+            ("tui.stench", "clue.stench"),
+            ("tui.breeze", "clue.breeze"),
+            ("tui.squeak", "clue.squeak"),
+        ]
+        .map(|(letter, word)| format!("{} {}", self.tr(letter), self.tr(word)))
+        .join("  ");
+        let seed = self.text(&Message::new("hud.seed").arg("seed", model.seed()));
+        let right = format!("{}   {}", legend, seed);
+        let right_x = width as i32 - right.chars().count() as i32 - 1;
+        canvas.text(right_x, top + 1, &right, Style::plain(Color::DarkGrey));
+
+        let (message, style) = if model.game_over {
+            let key = if model.win { "play.won" } else { "play.lost" };
+            let color = if model.win { Color::Green } else { Color::Red };
+            let text = format!(
+                "{} {}",
+                self.translations.format_all(&model.message),
+                self.tr(key)
+            );
+            (text, Style::bold(color))
+        } else {
+            (
+                self.translations.format_all(&model.message),
+                Style::plain(Color::White),
+            )
+        };
+        canvas.text(1, top + 2, &message, style);
+
+        // The latest events, oldest first; those the player could not see
+        // are only shown once the game is over
+        let log: Vec<_> = model
+            .log()
+            .iter()
+            .filter(|entry| model.game_over || !entry.event.is_hidden())
+            .collect();
+        let first = log.len().saturating_sub(LOG_LINES);
+        for (i, entry) in log[first..].iter().enumerate() {
+            let line = format!(
+                "{:>6.1}s  {}",
+                entry.time,
+                self.text(&entry.event.message())
+            );
+            canvas.text(1, top + 3 + i as i32, &line, Style::plain(Color::Grey));
+        }
+
+        canvas.text(
+            1,
+            top + PANEL_HEIGHT as i32 - 1,
+            self.tr("tui.help"),
+            Style::plain(Color::DarkGrey),
+        );
+    }
+}
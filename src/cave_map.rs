@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+/// What the player sensed while standing in a room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Clues {
+    /// The wumpus is in an adjacent room.
+    pub stench: bool,
+    /// A pit is in an adjacent room.
+    pub breeze: bool,
+    /// Bats are in an adjacent room.
+    pub squeak: bool,
+}
+
+impl Clues {
+    pub fn is_empty(&self) -> bool {
+        !(self.stench || self.breeze || self.squeak)
+    }
+}
+
+/// The exits a room's clues come from, e.g. the tunnels the breeze blows
+/// through, for front-ends that render clues with a direction.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ClueSources {
+    pub stench: Vec<usize>,
+    pub breeze: Vec<usize>,
+    pub squeak: Vec<usize>,
+}
+
+/// Suspicion the player can pin on a room they have not explored yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Marker {
+    Pit,
+    Bats,
+    Wumpus,
+}
+
+impl Marker {
+    pub const ALL: [Marker; 3] = [Marker::Pit, Marker::Bats, Marker::Wumpus];
+}
+
+/// The map the player builds while exploring: visited rooms, the clues
+/// sensed in each of them and the markers put on unexplored rooms.
+///
+/// It only holds what the player could know, never the hidden hazards.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CaveMap {
+    clues: Vec<Option<Clues>>,
+    markers: Vec<Vec<Marker>>,
+}
+
+impl CaveMap {
+    pub fn new(num_rooms: usize) -> Self {
+        CaveMap {
+            clues: vec![None; num_rooms],
+            markers: vec![Vec::new(); num_rooms],
+        }
+    }
+
+    /// Records a visit to `room` and what was sensed there. Markers on the
+    /// room are dropped, since it is no longer a guess.
+    pub fn visit(&mut self, room: usize, clues: Clues) {
+        self.clues[room] = Some(clues);
+        self.markers[room].clear();
+    }
+
+    /// Whether the map has exactly one entry per room of a cave this size.
+    pub(crate) fn fits(&self, num_rooms: usize) -> bool {
+        self.clues.len() == num_rooms && self.markers.len() == num_rooms
+    }
+
+    pub fn is_visited(&self, room: usize) -> bool {
+        self.clues[room].is_some()
+    }
+
+    /// Clues sensed in `room`, or None if it was never visited.
+    pub fn clues(&self, room: usize) -> Option<Clues> {
+        self.clues[room]
+    }
+
+    pub fn visited_rooms(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.clues.len()).filter(|&room| self.is_visited(room))
+    }
+
+    pub fn markers(&self, room: usize) -> &[Marker] {
+        &self.markers[room]
+    }
+
+    /// Adds or removes a marker on an unexplored room. Visited rooms cannot
+    /// be marked and are left untouched.
+    pub fn toggle_marker(&mut self, room: usize, marker: Marker) {
+        if self.is_visited(room) {
+            return;
+        }
+
+        let markers = &mut self.markers[room];
+        if let Some(i) = markers.iter().position(|&m| m == marker) {
+            markers.remove(i);
+        } else {
+            markers.push(marker);
+        }
+    }
+
+    /// Steps an unexplored room through no marker, pit, bats and wumpus.
+    pub fn cycle_marker(&mut self, room: usize) {
+        if self.is_visited(room) {
+            return;
+        }
+
+        let markers = &mut self.markers[room];
+        let next = match markers.as_slice() {
+            [] => Some(Marker::Pit),
+            [Marker::Pit] => Some(Marker::Bats),
+            [Marker::Bats] => Some(Marker::Wumpus),
+            _ => None,
+        };
+        markers.clear();
+        markers.extend(next);
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+/// Source of time for the rules engine, expressed in seconds.
+///
+/// The model never reads the wall clock directly, so front-ends, bots and
+/// tests can decide how time flows.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> f64;
+}
+
+/// Wall clock measuring the seconds elapsed since its creation.
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64()
+    }
+}
+
+/// Clock that only moves when explicitly advanced.
+pub struct ManualClock {
+    bits: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new(start: f64) -> Self {
+        ManualClock {
+            bits: AtomicU64::new(start.to_bits()),
+        }
+    }
+
+    pub fn set(&self, time: f64) {
+        self.bits.store(time.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, seconds: f64) {
+        self.set(self.now() + seconds);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new(0.0)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> f64 {
+        f64::from_bits(self.bits.load(Ordering::Relaxed))
+    }
+}
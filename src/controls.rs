@@ -0,0 +1,201 @@
+use crate::action::Action;
+use crate::game_model::{GameError, GameModel, MAX_ARROW_PATH};
+use crate::i18n::Message;
+use crate::solver::Solver;
+
+/// Device independent actions available while playing. Front-ends map keys,
+/// buttons and clicks to these, so every one of them plays the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayCommand {
+    ToggleShootMode,
+    Cancel,
+    FocusPrevious,
+    FocusNext,
+    /// Picks the exit with the given index, as the number keys do.
+    ChooseExit(usize),
+    /// Picks a room directly, as a click does.
+    ChooseRoom(usize),
+    Confirm,
+    ExtendPath,
+    RemoveLastStep,
+    /// Shoots along the path built so far.
+    Fire,
+    CycleMarker,
+    /// Takes back the last action, in practice games only.
+    Undo,
+    Redo,
+    /// Suggests the next action, in practice games only.
+    Hint,
+}
+
+/// Interaction state of the play screen: whether the player is moving or
+/// aiming, the arrow path being built and the exit focused by the keyboard.
+#[derive(Debug, Clone, Default)]
+pub struct PlayControls {
+    pub shoot_mode: bool,
+    pub arrow_path: Vec<usize>,
+    pub focused_exit: usize,
+}
+
+impl PlayControls {
+    pub fn new() -> Self {
+        PlayControls {
+            shoot_mode: false,
+            arrow_path: Vec::with_capacity(MAX_ARROW_PATH),
+            focused_exit: 0,
+        }
+    }
+
+    /// Leaves shoot mode and forgets any selection, e.g. after loading a game.
+    pub fn reset(&mut self) {
+        self.shoot_mode = false;
+        self.arrow_path.clear();
+        self.focused_exit = 0;
+    }
+
+    /// Room whose exits can currently be chosen: the player's room while
+    /// moving, the last room of the arrow path while aiming.
+    pub fn exits_origin(&self, model: &GameModel) -> usize {
+        if self.shoot_mode {
+            self.arrow_path
+                .last()
+                .copied()
+                .unwrap_or(model.player_position)
+        } else {
+            model.player_position
+        }
+    }
+
+    pub fn exits<'a>(&self, model: &'a GameModel) -> &'a [usize] {
+        model.cave.exits(self.exits_origin(model))
+    }
+
+    pub fn focused_room(&self, model: &GameModel) -> Option<usize> {
+        self.exits(model).get(self.focused_exit).copied()
+    }
+
+    pub fn execute(&mut self, model: &mut GameModel, command: PlayCommand) {
+        match command {
+            PlayCommand::ToggleShootMode => self.toggle_shoot_mode(model),
+            PlayCommand::Cancel => {
+                if self.shoot_mode {
+                    self.toggle_shoot_mode(model);
+                }
+            }
+            PlayCommand::FocusPrevious => {
+                let count = self.exits(model).len();
+                self.focused_exit = (self.focused_exit + count - 1) % count;
+            }
+            PlayCommand::FocusNext => {
+                let count = self.exits(model).len();
+                self.focused_exit = (self.focused_exit + 1) % count;
+            }
+            PlayCommand::ChooseExit(i) => {
+                if let Some(&room) = self.exits(model).get(i) {
+                    self.choose_room(model, room);
+                }
+            }
+            PlayCommand::ChooseRoom(room) => self.choose_room(model, room),
+            PlayCommand::Confirm => {
+                if self.shoot_mode {
+                    if self.arrow_path.is_empty() {
+                        self.execute(model, PlayCommand::ExtendPath);
+                    }
+                    self.fire(model);
+                } else if let Some(room) = self.focused_room(model) {
+                    self.choose_room(model, room);
+                }
+            }
+            PlayCommand::ExtendPath => {
+                if let Some(room) = self.focused_room(model) {
+                    self.choose_room(model, room);
+                }
+            }
+            PlayCommand::RemoveLastStep => {
+                if self.shoot_mode {
+                    self.arrow_path.pop();
+                    self.focused_exit = 0;
+                }
+            }
+            PlayCommand::Fire => self.fire(model),
+            PlayCommand::CycleMarker => {
+                if let Some(room) = self.focused_room(model) {
+                    model.map.cycle_marker(room);
+                }
+            }
+            PlayCommand::Undo => self.take_back(model, GameModel::undo),
+            PlayCommand::Redo => self.take_back(model, GameModel::redo),
+            PlayCommand::Hint => self.hint(model),
+        }
+    }
+
+    fn toggle_shoot_mode(&mut self, model: &mut GameModel) {
+        if model.arrows > 0 {
+            self.shoot_mode = !self.shoot_mode;
+            self.arrow_path.clear();
+            self.focused_exit = 0;
+            if self.shoot_mode {
+                model.set_message(Message::new("mode.shoot").arg("max", MAX_ARROW_PATH));
+            } else {
+                model.generate_warnings();
+                model.message.insert(0, Message::new("mode.move"));
+            }
+        } else {
+            model.set_message(GameError::NoArrows.message());
+        }
+    }
+
+    // Moves the player to `room`, or adds it to the arrow path in shoot mode
+    fn choose_room(&mut self, model: &mut GameModel, room: usize) {
+        if self.shoot_mode {
+            if self.arrow_path.len() < MAX_ARROW_PATH {
+                self.arrow_path.push(room);
+            }
+        } else if let Err(err) = model.apply(Action::Move(room)) {
+            model.set_message(err.message());
+        }
+        self.focused_exit = 0;
+    }
+
+    // Undoes or redoes an action, dropping any arrow path being built
+    fn take_back(
+        &mut self,
+        model: &mut GameModel,
+        step: fn(&mut GameModel) -> Result<(), GameError>,
+    ) {
+        match step(model) {
+            Ok(()) => self.reset(),
+            Err(err) => model.set_message(err.message()),
+        }
+    }
+
+    // Shows what the solver would do, and focuses the room it would move to
+    fn hint(&mut self, model: &mut GameModel) {
+        if !model.is_practice() {
+            model.set_message(Message::new("hint.practice_only"));
+            return;
+        }
+        let Some(decision) = Solver::hint(model) else {
+            model.set_message(GameError::GameAlreadyOver.message());
+            return;
+        };
+        if let Action::Move(room) = decision.action
+            && !self.shoot_mode
+            && let Some(i) = self.exits(model).iter().position(|&exit| exit == room)
+        {
+            self.focused_exit = i;
+        }
+        model.set_message(decision.message());
+    }
+
+    fn fire(&mut self, model: &mut GameModel) {
+        if !self.arrow_path.is_empty() {
+            if let Err(err) = model.apply(Action::Shoot(self.arrow_path.clone())) {
+                model.set_message(err.message());
+            }
+            self.arrow_path.clear();
+            self.shoot_mode = false;
+            self.focused_exit = 0;
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::action::Action;
+use crate::clock::{Clock, ManualClock};
+use crate::difficulty::Difficulty;
+use crate::game_model::GameModel;
+use crate::i18n::Message;
+use crate::stats::Outcome;
+use crate::topology::CaveKind;
+
+/// Every daily cave is played with the same rules, so that results can be
+/// compared.
+pub const DAILY_DIFFICULTY: Difficulty = Difficulty::Classic;
+pub const DAILY_CAVE: CaveKind = CaveKind::Dodecahedron;
+
+// Opening words of every result, also the mark of a result to verify
+const SHARE_PREFIX: &str = "Wumpus daily";
+
+/// A day of the calendar, in UTC so that the whole world shares the same
+/// daily cave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Date::from_days((seconds / 86_400) as i64)
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        // Civil calendar from a day count, in eras of 400 years that start
+        // on March 1st so that leap days come last
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Date { year, month, day }
+    }
+
+    /// Days from 1970-01-01 to this date.
+    pub fn days(self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let mp = if month > 2 { month - 3 } else { month + 9 };
+        let doy = (153 * mp + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Seed of the daily cave of this date.
+    pub fn seed(self) -> u64 {
+        // SplitMix64 scrambles consecutive days into unrelated seeds, and
+        // unlike the standard hasher it never changes between versions
+        let mut z = (self.days() as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = ShareError;
+
+    /// Parses a date written as `YYYY-MM-DD`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.splitn(3, '-');
+        let mut next = || parts.next().and_then(|part| part.parse().ok());
+        let (Some(year), Some(month), Some(day)) = (next(), next(), next()) else {
+            return Err(ShareError::Malformed);
+        };
+        let date = Date {
+            year: year as i32,
+            month,
+            day,
+        };
+        // Only real days survive the round trip through the day count
+        if Date::from_days(date.days()) != date {
+            return Err(ShareError::Malformed);
+        }
+        Ok(date)
+    }
+}
+
+/// The daily cave of `date`, the same for every player.
+pub fn daily_game(date: Date, clock: Arc<dyn Clock>) -> GameModel {
+    let mut model = GameModel::with_seed_and_clock(date.seed(), clock);
+    model.set_cave_kind(DAILY_CAVE);
+    model.set_difficulty(DAILY_DIFFICULTY);
+    model
+}
+
+/// Result of a finished daily cave as one line of plain text, e.g.
+/// `Wumpus daily 2024-05-01: won, moves 7, arrows 2 [m5 m6 s7-8]`.
+///
+/// The rooms between the brackets are every move and shot played, so that
+/// anyone can check the result with [`verify`]. `None` until the game is
+/// over.
+pub fn share(model: &GameModel, date: Date) -> Option<String> {
+    let outcome = model.outcome()?;
+    let stats = model.stats();
+    let actions = model
+        .log()
+        .iter()
+        .filter_map(|entry| Action::from_event(&entry.event))
+        .map(|action| action_word(&action))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(format!(
+        "{} {}: {}, moves {}, arrows {} [{}]",
+        SHARE_PREFIX,
+        date,
+        outcome_word(outcome),
+        stats.moves,
+        stats.shots,
+        actions
+    ))
+}
+
+/// Why a shared result does not hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareError {
+    /// The text is not a result written by [`share`].
+    Malformed,
+    /// The moves listed do not lead to the result claimed.
+    Mismatch,
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::Malformed => write!(f, "risultato non leggibile"),
+            ShareError::Mismatch => write!(f, "le mosse non portano al risultato dichiarato"),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+impl ShareError {
+    /// Description of the error for the player.
+    pub fn message(&self) -> Message {
+        match self {
+            ShareError::Malformed => Message::new("daily.malformed"),
+            ShareError::Mismatch => Message::new("daily.mismatch"),
+        }
+    }
+}
+
+/// Checks a result written by [`share`] by replaying its moves in the
+/// daily cave of its date. Returns the date and the outcome.
+pub fn verify(result: &str) -> Result<(Date, Outcome), ShareError> {
+    let rest = result
+        .trim()
+        .strip_prefix(SHARE_PREFIX)
+        .ok_or(ShareError::Malformed)?;
+    let (date, rest) = rest.trim().split_once(':').ok_or(ShareError::Malformed)?;
+    let date: Date = date.parse()?;
+    let (summary, actions) = rest.split_once('[').ok_or(ShareError::Malformed)?;
+    let actions = actions.strip_suffix(']').ok_or(ShareError::Malformed)?;
+
+    let mut fields = summary.split(',').map(str::trim);
+    let outcome = fields
+        .next()
+        .and_then(outcome_from_word)
+        .ok_or(ShareError::Malformed)?;
+    let mut count = |unit: &str| {
+        fields
+            .next()
+            .and_then(|field| field.strip_prefix(unit))
+            .and_then(|number| number.trim().parse::<u32>().ok())
+            .ok_or(ShareError::Malformed)
+    };
+    let moves = count("moves")?;
+    let shots = count("arrows")?;
+
+    // Time plays no part in the result, so a still clock will do
+    let mut model = daily_game(date, Arc::new(ManualClock::new(0.0)));
+    for word in actions.split_whitespace() {
+        model
+            .apply(action_from_word(word)?)
+            .map_err(|_| ShareError::Mismatch)?;
+    }
+
+    let stats = model.stats();
+    if model.outcome() != Some(outcome) || stats.moves != moves || stats.shots != shots {
+        return Err(ShareError::Mismatch);
+    }
+    Ok((date, outcome))
+}
+
+// An action as written in a shared result: `m5` moves to room 5, `s7-8`
+// shoots through rooms 7 and 8
+fn action_word(action: &Action) -> String {
+    match action {
+        Action::Move(room) => format!("m{}", room),
+        Action::Shoot(path) => format!(
+            "s{}",
+            path.iter()
+                .map(|room| room.to_string())
+                .collect::<Vec<_>>()
+                .join("-")
+        ),
+    }
+}
+
+fn action_from_word(word: &str) -> Result<Action, ShareError> {
+    let rooms = |list: &str| -> Result<Vec<usize>, ShareError> {
+        list.split('-')
+            .map(|room| room.parse().map_err(|_| ShareError::Malformed))
+            .collect()
+    };
+    if let Some(room) = word.strip_prefix('m') {
+        match rooms(room)?.as_slice() {
+            [room] => Ok(Action::Move(*room)),
+            _ => Err(ShareError::Mismatch),
+        }
+    } else if let Some(path) = word.strip_prefix('s') {
+        Ok(Action::Shoot(rooms(path)?))
+    } else {
+        Err(ShareError::Malformed)
+    }
+}
+
+// Words of the outcomes in shared results, which stay in English whatever
+// the language of the game so that anyone can verify them
+fn outcome_word(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Won => "won",
+        Outcome::EatenByWumpus => "eaten",
+        Outcome::FellInPit => "pit",
+        Outcome::ShotSelf => "own arrow",
+        Outcome::OutOfArrows => "out of arrows",
+    }
+}
+
+fn outcome_from_word(word: &str) -> Option<Outcome> {
+    [
+        Outcome::Won,
+        Outcome::EatenByWumpus,
+        Outcome::FellInPit,
+        Outcome::ShotSelf,
+        Outcome::OutOfArrows,
+    ]
+    .into_iter()
+    .find(|&outcome| outcome_word(outcome) == word)
+}
+
+/// The daily caves played on this computer, to allow one attempt a day.
+///
+/// A day is marked as soon as its cave starts, so quitting does not grant
+/// another attempt; its result is added once the game is over.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyHistory {
+    /// Result of each day played, by date; `None` if it was abandoned.
+    attempts: BTreeMap<String, Option<String>>,
+}
+
+impl DailyHistory {
+    pub fn has_played(&self, date: Date) -> bool {
+        self.attempts.contains_key(&date.to_string())
+    }
+
+    /// The shared result of the day, once its game is over.
+    pub fn result(&self, date: Date) -> Option<&str> {
+        self.attempts.get(&date.to_string())?.as_deref()
+    }
+
+    /// Uses up the attempt of the day; false if it was already used.
+    pub fn start(&mut self, date: Date) -> bool {
+        if self.has_played(date) {
+            return false;
+        }
+        self.attempts.insert(date.to_string(), None);
+        true
+    }
+
+    pub fn finish(&mut self, date: Date, result: String) {
+        self.attempts.insert(date.to_string(), Some(result));
+    }
+
+    /// Reads the history from `path`; a missing file is an empty history.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(DailyHistory::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the history to `path`, creating the parent directories if
+    /// needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent;
+    use crate::solver::Solver;
+
+    fn date(text: &str) -> Date {
+        text.parse().unwrap()
+    }
+
+    // The daily cave of `date`, played to the end by the solver
+    fn played(date: Date) -> GameModel {
+        let mut model = daily_game(date, Arc::new(ManualClock::new(0.0)));
+        let mut solver = Solver::new(model.difficulty);
+        agent::play(&mut model, &mut solver, 1000);
+        model
+    }
+
+    #[test]
+    fn days_round_trip() {
+        assert_eq!(Date::from_days(0), date("1970-01-01"));
+        assert_eq!(date("2024-02-29").days(), 19_782);
+        assert_eq!(date("1969-12-31").days(), -1);
+        for days in (-800_000..800_000).step_by(97) {
+            assert_eq!(Date::from_days(days).days(), days);
+        }
+    }
+
+    #[test]
+    fn only_real_days_parse() {
+        for text in ["2023-02-29", "2024-13-01", "2024-04-31", "2024-05", "today"] {
+            assert_eq!(text.parse::<Date>(), Err(ShareError::Malformed));
+        }
+        assert_eq!(date("2000-02-29").to_string(), "2000-02-29");
+    }
+
+    #[test]
+    fn shared_results_verify() {
+        for days in 19_700..19_720 {
+            let date = Date::from_days(days);
+            let model = played(date);
+            let result = share(&model, date).unwrap();
+            assert_eq!(verify(&result), Ok((date, model.outcome().unwrap())));
+        }
+    }
+
+    #[test]
+    fn nothing_to_share_before_the_end() {
+        let date = date("2024-05-01");
+        let model = daily_game(date, Arc::new(ManualClock::new(0.0)));
+        assert_eq!(share(&model, date), None);
+    }
+
+    #[test]
+    fn malformed_results_are_rejected() {
+        for result in [
+            "",
+            "hello",
+            "Wumpus daily 2024-05-01 won, moves 1, arrows 0 []",
+            "Wumpus daily 2024-05-32: won, moves 1, arrows 0 []",
+            "Wumpus daily 2024-05-01: lost, moves 1, arrows 0 []",
+            "Wumpus daily 2024-05-01: won, moves one, arrows 0 []",
+            "Wumpus daily 2024-05-01: won, moves 1, arrows 0 [m1",
+            "Wumpus daily 2024-05-01: won, moves 1, arrows 0 [x1]",
+        ] {
+            assert_eq!(verify(result), Err(ShareError::Malformed), "{}", result);
+        }
+    }
+
+    #[test]
+    fn tampered_results_are_rejected() {
+        let date = date("2024-05-01");
+        let result = share(&played(date), date).unwrap();
+        let (summary, actions) = result.split_once('[').unwrap();
+
+        let boasted = summary.replace("moves ", "moves 1");
+        assert_eq!(
+            verify(&format!("{}[{}", boasted, actions)),
+            Err(ShareError::Mismatch)
+        );
+
+        let unplayed = format!("{}[]", summary);
+        assert_eq!(verify(&unplayed), Err(ShareError::Mismatch));
+
+        for impossible in ["m999", "m1-2", "s1-2-1"] {
+            let result = format!("{}[{}]", summary, impossible);
+            assert_eq!(verify(&result), Err(ShareError::Mismatch), "{}", result);
+        }
+    }
+}
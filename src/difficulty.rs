@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+/// How hard the cave is to survive, chosen before the game starts.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum Difficulty {
+    Easy,
+    /// The rules of the original game.
+    #[default]
+    Classic,
+    Hard,
+    Nightmare,
+}
+
+/// The rules that change with the difficulty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rules {
+    pub bats: usize,
+    pub pits: usize,
+    pub arrows: u32,
+    /// Chance that a missed arrow wakes the wumpus, from 0 to 1.
+    pub wake_chance: f32,
+    /// The wumpus moves to an adjacent room every this many turns, if set.
+    pub wander_every: Option<u32>,
+    /// Whether bats fly off to another room after carrying the player.
+    pub bats_relocate: bool,
+    /// Whether bats only drop the player in rooms without the wumpus or a
+    /// pit.
+    pub safe_drop: bool,
+}
+
+impl Rules {
+    /// Message key telling what bats do after carrying the player.
+    pub fn bats_key(&self) -> &'static str {
+        if self.bats_relocate {
+            "difficulty.bats_relocate"
+        } else {
+            "difficulty.bats_stay"
+        }
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Classic,
+        Difficulty::Hard,
+        Difficulty::Nightmare,
+    ];
+
+    pub fn rules(self) -> Rules {
+        match self {
+            Difficulty::Easy => Rules {
+                bats: 1,
+                pits: 1,
+                arrows: 7,
+                wake_chance: 0.5,
+                wander_every: None,
+                bats_relocate: false,
+                safe_drop: true,
+            },
+            Difficulty::Classic => Rules {
+                bats: 2,
+                pits: 2,
+                arrows: 5,
+                wake_chance: 0.75,
+                wander_every: None,
+                bats_relocate: false,
+                safe_drop: false,
+            },
+            Difficulty::Hard => Rules {
+                bats: 3,
+                pits: 3,
+                arrows: 4,
+                wake_chance: 0.9,
+                wander_every: Some(5),
+                bats_relocate: true,
+                safe_drop: false,
+            },
+            Difficulty::Nightmare => Rules {
+                bats: 3,
+                pits: 4,
+                arrows: 3,
+                wake_chance: 1.0,
+                wander_every: Some(2),
+                bats_relocate: true,
+                safe_drop: false,
+            },
+        }
+    }
+
+    /// Message key of the difficulty's name.
+    pub fn key(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Classic => "difficulty.classic",
+            Difficulty::Hard => "difficulty.hard",
+            Difficulty::Nightmare => "difficulty.nightmare",
+        }
+    }
+
+    /// Parses an English name such as `hard`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|d| format!("{:?}", d).eq_ignore_ascii_case(name))
+    }
+
+    pub fn next(self) -> Self {
+        let i = Difficulty::ALL.iter().position(|&d| d == self).unwrap();
+        Difficulty::ALL[(i + 1) % Difficulty::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let i = Difficulty::ALL.iter().position(|&d| d == self).unwrap();
+        Difficulty::ALL[(i + Difficulty::ALL.len() - 1) % Difficulty::ALL.len()]
+    }
+}
@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+use crate::difficulty::Difficulty;
+use crate::i18n::Message;
+use crate::topology::CaveKind;
+
+/// Something that happened during a game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// A new game began; records everything needed to replay it.
+    GameStarted {
+        seed: u64,
+        cave: CaveKind,
+        /// Missing from logs written before difficulty levels existed.
+        #[serde(default)]
+        difficulty: Difficulty,
+        player: usize,
+        wumpus: usize,
+        bats: Vec<usize>,
+        pits: Vec<usize>,
+    },
+    /// The player walked through a tunnel.
+    Moved {
+        from: usize,
+        to: usize,
+    },
+    /// A bat snatched the player and dropped them elsewhere.
+    CarriedByBats {
+        from: usize,
+        to: usize,
+    },
+    /// From `room` the player smelled the wumpus in a nearby room.
+    SensedStench {
+        room: usize,
+    },
+    /// From `room` the player felt the draught of a nearby pit.
+    SensedBreeze {
+        room: usize,
+    },
+    /// From `room` the player heard bats in a nearby room.
+    SensedBats {
+        room: usize,
+    },
+    /// The bats moved to another room after carrying the player.
+    BatsMoved {
+        from: usize,
+        to: usize,
+    },
+    /// The player shot an arrow. `flight` lists the rooms it actually went
+    /// through, which differs from `path` when it was deflected.
+    ArrowShot {
+        path: Vec<usize>,
+        flight: Vec<usize>,
+    },
+    WumpusKilled {
+        room: usize,
+    },
+    /// The arrow flew back into the player's room.
+    ShotSelf {
+        room: usize,
+    },
+    ArrowMissed,
+    /// The missed shot woke the wumpus, which moved to another room.
+    WumpusWoke {
+        from: usize,
+        to: usize,
+    },
+    /// The wumpus wandered to another room on its own.
+    WumpusWandered {
+        from: usize,
+        to: usize,
+    },
+    EatenByWumpus {
+        room: usize,
+    },
+    FellInPit {
+        room: usize,
+    },
+    OutOfArrows,
+}
+
+impl GameEvent {
+    /// Whether the event is a choice of the player rather than a
+    /// consequence of one.
+    pub fn is_player_action(&self) -> bool {
+        matches!(self, GameEvent::Moved { .. } | GameEvent::ArrowShot { .. })
+    }
+
+    /// Whether the event is a clue sensed on entering a room.
+    pub fn is_sensation(&self) -> bool {
+        matches!(
+            self,
+            GameEvent::SensedStench { .. }
+                | GameEvent::SensedBreeze { .. }
+                | GameEvent::SensedBats { .. }
+        )
+    }
+
+    /// Whether the event happened out of the player's sight: telling it
+    /// while the game is on would give away where the wumpus or the bats
+    /// are.
+    pub fn is_hidden(&self) -> bool {
+        matches!(
+            self,
+            GameEvent::BatsMoved { .. }
+                | GameEvent::WumpusWoke { .. }
+                | GameEvent::WumpusWandered { .. }
+        )
+    }
+
+    /// Short description of the event, for logs and replays.
+    pub fn message(&self) -> Message {
+        match self {
+            GameEvent::GameStarted { cave, player, .. } => Message::new("event.started")
+                .arg("cave", cave.key())
+                .arg("room", player),
+            GameEvent::Moved { from, to } => {
+                Message::new("event.moved").arg("from", from).arg("to", to)
+            }
+            GameEvent::CarriedByBats { from, to } => Message::new("event.carried_by_bats")
+                .arg("from", from)
+                .arg("to", to),
+            GameEvent::SensedStench { room } => {
+                Message::new("event.sensed_stench").arg("room", room)
+            }
+            GameEvent::SensedBreeze { room } => {
+                Message::new("event.sensed_breeze").arg("room", room)
+            }
+            GameEvent::SensedBats { room } => Message::new("event.sensed_bats").arg("room", room),
+            GameEvent::BatsMoved { from, to } => Message::new("event.bats_moved")
+                .arg("from", from)
+                .arg("to", to),
+            GameEvent::ArrowShot { flight, .. } => {
+                let rooms = flight
+                    .iter()
+                    .map(|room| room.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Message::new("event.arrow_shot").arg("rooms", rooms)
+            }
+            GameEvent::WumpusKilled { room } => {
+                Message::new("event.wumpus_killed").arg("room", room)
+            }
+            GameEvent::ShotSelf { room } => Message::new("event.shot_self").arg("room", room),
+            GameEvent::ArrowMissed => Message::new("event.arrow_missed"),
+            GameEvent::WumpusWoke { from, to } => Message::new("event.wumpus_woke")
+                .arg("from", from)
+                .arg("to", to),
+            GameEvent::WumpusWandered { from, to } => Message::new("event.wumpus_wandered")
+                .arg("from", from)
+                .arg("to", to),
+            GameEvent::EatenByWumpus { room } => {
+                Message::new("event.eaten_by_wumpus").arg("room", room)
+            }
+            GameEvent::FellInPit { room } => Message::new("event.fell_in_pit").arg("room", room),
+            GameEvent::OutOfArrows => Message::new("event.out_of_arrows"),
+        }
+    }
+}
+
+/// What the events of an action, as returned by
+/// [`GameModel::move_player`](crate::GameModel::move_player) and
+/// [`GameModel::shoot_arrow`](crate::GameModel::shoot_arrow), tell the
+/// player: one sentence per message, in the order things happened.
+pub fn describe(events: &[GameEvent]) -> Vec<Message> {
+    // The bats settling elsewhere go unnoticed by the player
+    let noticed = |event: &&GameEvent| !matches!(event, GameEvent::BatsMoved { .. });
+    let mut messages = Vec::new();
+    let mut flight: &[usize] = &[];
+
+    for (i, event) in events.iter().enumerate() {
+        let previous = events[..i].iter().rev().find(noticed);
+        let mut following = events[i + 1..].iter().filter(noticed).peekable();
+        match event {
+            GameEvent::GameStarted { .. } => messages.push(Message::new("welcome")),
+            GameEvent::Moved { to, .. } | GameEvent::CarriedByBats { to, .. } => {
+                if let GameEvent::CarriedByBats { .. } = event {
+                    messages.push(Message::new("move.carried_by_bats").arg("room", to));
+                }
+                // Unless a bat or a hazard awaits, this is where the player
+                // stops and looks around, while the wumpus may wander unseen
+                let mut following = following.filter(|event| !event.is_hidden()).peekable();
+                if matches!(
+                    following.peek(),
+                    Some(
+                        GameEvent::CarriedByBats { .. }
+                            | GameEvent::EatenByWumpus { .. }
+                            | GameEvent::FellInPit { .. }
+                    )
+                ) {
+                    continue;
+                }
+                messages.push(Message::new("room.current").arg("room", to));
+                let mut quiet = true;
+                for sensation in following.take_while(|event| event.is_sensation()) {
+                    quiet = false;
+                    messages.push(warning(sensation));
+                }
+                if quiet {
+                    messages.push(Message::new("room.quiet"));
+                }
+            }
+            GameEvent::EatenByWumpus { .. } => messages.push(Message::new(match previous {
+                Some(GameEvent::WumpusWoke { .. }) => "shoot.woken_wumpus_found_you",
+                Some(GameEvent::WumpusWandered { .. }) => "move.wumpus_wandered_in",
+                Some(GameEvent::CarriedByBats { .. }) => "move.dropped_on_wumpus",
+                _ => "move.eaten",
+            })),
+            GameEvent::FellInPit { .. } => messages.push(Message::new(match previous {
+                Some(GameEvent::CarriedByBats { .. }) => "move.dropped_in_pit",
+                _ => "move.fell_in_pit",
+            })),
+            GameEvent::ArrowShot { flight: rooms, .. } => flight = rooms,
+            GameEvent::WumpusKilled { .. } => messages.push(Message::new("shoot.hit_wumpus")),
+            GameEvent::ShotSelf { .. } => messages.push(Message::new("shoot.hit_self")),
+            GameEvent::ArrowMissed => {
+                let rooms = flight
+                    .iter()
+                    .map(|room| room.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                messages.push(Message::new("shoot.missed").arg("rooms", rooms));
+            }
+            GameEvent::WumpusWoke { .. } => {
+                if !matches!(following.peek(), Some(GameEvent::EatenByWumpus { .. })) {
+                    messages.push(Message::new("shoot.wumpus_moved"));
+                }
+            }
+            GameEvent::OutOfArrows => messages.push(Message::new("shoot.out_of_arrows")),
+            // Clues sensed again where the player stands, after the wumpus
+            // moved during a shot
+            GameEvent::SensedStench { .. }
+            | GameEvent::SensedBreeze { .. }
+            | GameEvent::SensedBats { .. }
+                if !events[..i]
+                    .iter()
+                    .any(|event| matches!(event, GameEvent::Moved { .. })) =>
+            {
+                messages.push(warning(event));
+            }
+            GameEvent::SensedStench { .. }
+            | GameEvent::SensedBreeze { .. }
+            | GameEvent::SensedBats { .. }
+            | GameEvent::BatsMoved { .. }
+            | GameEvent::WumpusWandered { .. } => {}
+        }
+    }
+    messages
+}
+
+// The warning of a sensed clue
+fn warning(sensation: &GameEvent) -> Message {
+    Message::new(match sensation {
+        GameEvent::SensedStench { .. } => "warning.stench",
+        GameEvent::SensedBreeze { .. } => "warning.breeze",
+        _ => "warning.squeak",
+    })
+}
+
+/// An event with the moment it happened, in seconds on the game clock.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub time: f64,
+    pub event: GameEvent,
+}
+
+/// Writes a game log to `path` as JSON.
+pub fn export_log(log: &[LogEntry], path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(log)?)
+}
+
+/// Reads a game log written by [`export_log`].
+pub fn import_log(path: &Path) -> io::Result<Vec<LogEntry>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_flights_read_as_room_numbers() {
+        let event = GameEvent::ArrowShot {
+            path: vec![3, 7, 8],
+            flight: vec![3, 7, 12],
+        };
+        let message = event.message();
+        assert_eq!(message.key, "event.arrow_shot");
+        assert_eq!(message.args, [("rooms".to_string(), "3 7 12".to_string())]);
+    }
+}
@@ -1,54 +1,139 @@
-use crate::{game_model::GameModel, game_view::GameView};
+use crate::game_view::{GameView, virtual_mouse_position};
+use crate::gamepad::{GamepadButton, GamepadInput};
 use macroquad::prelude::*; // Update the import to use the new module
+use rustedbytes_wumpus::{GameModel, PlayCommand, PlayControls};
+
+// Number keys used as shortcuts for the exits of the current room
+const EXIT_KEYS: [[KeyCode; 2]; 9] = [
+    [KeyCode::Key1, KeyCode::Kp1],
+    [KeyCode::Key2, KeyCode::Kp2],
+    [KeyCode::Key3, KeyCode::Kp3],
+    [KeyCode::Key4, KeyCode::Kp4],
+    [KeyCode::Key5, KeyCode::Kp5],
+    [KeyCode::Key6, KeyCode::Kp6],
+    [KeyCode::Key7, KeyCode::Kp7],
+    [KeyCode::Key8, KeyCode::Kp8],
+    [KeyCode::Key9, KeyCode::Kp9],
+];
 
 pub struct GameController {
-    pub shoot_mode: bool,
+    pub controls: PlayControls,
     pub selected_room: Option<usize>,
+    pub gamepad: GamepadInput,
     pub view: GameView, // Reference to the view for reusing get_clicked_room
 }
 
 impl GameController {
     pub fn new() -> Self {
         GameController {
-            shoot_mode: false,
+            controls: PlayControls::new(),
             selected_room: None,
+            gamepad: GamepadInput::new(),
             view: GameView::new(),
         }
     }
 
+    /// Leaves shoot mode and forgets any selection, e.g. after loading a game.
+    pub fn reset(&mut self) {
+        self.controls.reset();
+    }
+
+    pub fn exits<'a>(&self, model: &'a GameModel) -> &'a [usize] {
+        self.controls.exits(model)
+    }
+
+    pub fn focused_room(&self, model: &GameModel) -> Option<usize> {
+        self.controls.focused_room(model)
+    }
+
     pub fn process_input_play(&mut self, model: &mut GameModel) {
-        // Toggle mode (movement or shooting)
-        if is_key_pressed(KeyCode::Space) {
-            if model.has_arrow {
-                self.shoot_mode = !self.shoot_mode;
-                if self.shoot_mode {
-                    model.message = String::from(
-                        "Modalità tiro attivata. Clicca su una stanza adiacente per tirare la freccia.",
-                    );
-                } else {
-                    model.message = String::from("Modalità movimento attivata.");
-                    model.generate_warnings();
-                }
+        for command in self.collect_commands() {
+            // A marker goes on the room under the cursor, if any
+            match (command, self.selected_room) {
+                (PlayCommand::CycleMarker, Some(room)) => model.map.cycle_marker(room),
+                _ => self.controls.execute(model, command),
+            }
+        }
+
+        // The right mouse button fires the arrow, or marks a room while moving
+        if is_mouse_button_pressed(MouseButton::Right) {
+            if self.controls.shoot_mode {
+                self.controls.execute(model, PlayCommand::Fire);
             } else {
-                model.message = String::from("Non hai più frecce!");
+                let mouse_pos = virtual_mouse_position();
+                if let Some(room) = self.view.get_clicked_room(model, mouse_pos) {
+                    model.map.cycle_marker(room);
+                }
             }
         }
 
         // Handle mouse click
         if is_mouse_button_pressed(MouseButton::Left) {
-            let mouse_pos = Vec2::new(mouse_position().0, mouse_position().1);
+            let mouse_pos = virtual_mouse_position();
             if let Some(room) = self.view.get_clicked_room(model, mouse_pos) {
-                if self.shoot_mode {
-                    model.shoot_arrow(room);
-                    self.shoot_mode = false;
-                } else {
-                    model.move_player(room);
-                }
+                self.controls.execute(model, PlayCommand::ChooseRoom(room));
             }
         }
 
         // Highlight the room under the cursor
-        let mouse_pos = Vec2::new(mouse_position().0, mouse_position().1);
+        let mouse_pos = virtual_mouse_position();
         self.selected_room = self.view.get_clicked_room(model, mouse_pos);
     }
+
+    fn collect_commands(&self) -> Vec<PlayCommand> {
+        let mut commands = Vec::new();
+
+        if is_key_pressed(KeyCode::Space) || self.gamepad.is_pressed(GamepadButton::North) {
+            commands.push(PlayCommand::ToggleShootMode);
+        }
+        if is_key_pressed(KeyCode::Escape) || self.gamepad.is_pressed(GamepadButton::East) {
+            commands.push(PlayCommand::Cancel);
+        }
+        if is_key_pressed(KeyCode::Left)
+            || is_key_pressed(KeyCode::Up)
+            || self.gamepad.is_pressed(GamepadButton::DPadLeft)
+            || self.gamepad.is_pressed(GamepadButton::DPadUp)
+        {
+            commands.push(PlayCommand::FocusPrevious);
+        }
+        if is_key_pressed(KeyCode::Right)
+            || is_key_pressed(KeyCode::Down)
+            || is_key_pressed(KeyCode::Tab)
+            || self.gamepad.is_pressed(GamepadButton::DPadRight)
+            || self.gamepad.is_pressed(GamepadButton::DPadDown)
+        {
+            commands.push(PlayCommand::FocusNext);
+        }
+        for (i, keys) in EXIT_KEYS.iter().enumerate() {
+            if keys.iter().any(|&key| is_key_pressed(key)) {
+                commands.push(PlayCommand::ChooseExit(i));
+            }
+        }
+        if is_key_pressed(KeyCode::Enter)
+            || is_key_pressed(KeyCode::KpEnter)
+            || self.gamepad.is_pressed(GamepadButton::South)
+        {
+            commands.push(PlayCommand::Confirm);
+        }
+        if self.gamepad.is_pressed(GamepadButton::West) {
+            commands.push(PlayCommand::ExtendPath);
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            commands.push(PlayCommand::RemoveLastStep);
+        }
+        if is_key_pressed(KeyCode::M) {
+            commands.push(PlayCommand::CycleMarker);
+        }
+        if is_key_pressed(KeyCode::Z) {
+            commands.push(PlayCommand::Undo);
+        }
+        if is_key_pressed(KeyCode::Y) {
+            commands.push(PlayCommand::Redo);
+        }
+        if is_key_pressed(KeyCode::H) {
+            commands.push(PlayCommand::Hint);
+        }
+
+        commands
+    }
 }
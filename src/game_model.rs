@@ -1,286 +1,1278 @@
-use ::rand::{
-    Rng, rng,
+use glam::Vec2;
+use rand::{
+    Rng, SeedableRng,
     seq::{IndexedRandom, SliceRandom},
 };
-use macroquad::prelude::*;
-use std::{collections::HashSet, f32::consts::PI};
+use rand_chacha::ChaCha8Rng;
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use crate::action::Action;
+use crate::cave_map::{CaveMap, ClueSources, Clues};
+use crate::clock::{Clock, SystemClock};
+use crate::difficulty::Difficulty;
+use crate::events::{self, GameEvent, LogEntry};
+use crate::i18n::Message;
+use crate::layout::{self, LayoutArea, LayoutStrategy, RING_RADII, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::save::{SAVE_VERSION, SaveError, SaveGame};
+use crate::stats::{GameStats, Outcome};
+use crate::topology::{CaveKind, CaveTopology};
 
 // Constants
-const NUM_ROOMS: usize = 20;
-const NUM_TUNNELS_PER_ROOM: usize = 3;
-const NUM_BATS: usize = 2;
-const NUM_PITS: usize = 2;
-const SCREEN_WIDTH: f32 = 800.0;
-const SCREEN_HEIGHT: f32 = 600.0;
-//const ROOM_RADIUS: f32 = 12.0;
-const NUM_RINGS: usize = 3;
-pub const RING_RADII: [f32; NUM_RINGS] = [200.0, 140.0, 60.0];
-//pub const LINE_THICKNESS: f32 = 2.0;
-pub const NODES_PER_RING: [usize; NUM_RINGS] = [5, 10, 5];
-pub const START_ANGLES: [f32; NUM_RINGS] = [-0.5 * PI, -0.5 * PI, 0.5 * PI];
+pub const MAX_ARROW_PATH: usize = 5;
+
+/// Why the model refused an action. A refused action changes nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// No tunnel leads from the player's room to this one.
+    NotAdjacent(usize),
+    NoArrows,
+    GameAlreadyOver,
+    /// The cave has no room with this number.
+    RoomOutOfRange(usize),
+    /// An arrow path must list from 1 to [`MAX_ARROW_PATH`] rooms.
+    PathTooLong,
+    /// The path turns straight back into the room it came from.
+    PathTooCrooked,
+    /// Actions can only be taken back in practice games.
+    NotPractice,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NotAdjacent(room) => {
+                write!(f, "nessun tunnel porta alla stanza {}", room)
+            }
+            GameError::NoArrows => write!(f, "frecce esaurite"),
+            GameError::GameAlreadyOver => write!(f, "la partita è già finita"),
+            GameError::RoomOutOfRange(room) => write!(f, "la stanza {} non esiste", room),
+            GameError::PathTooLong => write!(
+                f,
+                "il percorso della freccia deve avere da 1 a {} stanze",
+                MAX_ARROW_PATH
+            ),
+            GameError::PathTooCrooked => write!(f, "percorso della freccia troppo tortuoso"),
+            GameError::NotPractice => write!(f, "si annulla solo in allenamento"),
+            GameError::NothingToUndo => write!(f, "nessuna azione da annullare"),
+            GameError::NothingToRedo => write!(f, "nessuna azione da ripetere"),
+        }
+    }
+}
 
+impl std::error::Error for GameError {}
+
+impl GameError {
+    /// Description of the error for the player.
+    pub fn message(&self) -> Message {
+        match self {
+            GameError::NotAdjacent(room) => Message::new("move.not_adjacent").arg("room", room),
+            GameError::NoArrows => Message::new("shoot.no_arrows"),
+            GameError::GameAlreadyOver => Message::new("error.game_over"),
+            GameError::RoomOutOfRange(room) => Message::new("shoot.no_such_room").arg("room", room),
+            GameError::PathTooLong => Message::new("shoot.path_length").arg("max", MAX_ARROW_PATH),
+            GameError::PathTooCrooked => Message::new("shoot.too_crooked"),
+            GameError::NotPractice => Message::new("practice.only"),
+            GameError::NothingToUndo => Message::new("undo.nothing"),
+            GameError::NothingToRedo => Message::new("redo.nothing"),
+        }
+    }
+}
+
+/// Random number generator driving every chance event of a game.
+pub type GameRng = ChaCha8Rng;
+
+// States of a practice game before each action taken back or redone
+#[derive(Clone, Default)]
+struct Practice {
+    undo: Vec<GameModel>,
+    redo: Vec<GameModel>,
+}
+
+#[derive(Clone)]
 pub struct GameModel {
-    pub tunnels: [[usize; NUM_TUNNELS_PER_ROOM]; NUM_ROOMS],
+    pub cave: CaveTopology,
     pub player_position: usize,
     pub wumpus_position: usize,
     bat_positions: HashSet<usize>,
     pit_positions: HashSet<usize>,
     pub game_over: bool,
     pub win: bool,
-    pub has_arrow: bool,
-    pub message: String,
+    pub difficulty: Difficulty,
+    pub arrows: u32,
+    /// Feedback on the last action, one sentence per message.
+    pub message: Vec<Message>,
+    pub layout: LayoutStrategy,
     pub room_positions: Vec<Vec2>,
+    pub map: CaveMap,
+    log: Vec<LogEntry>,
+    // Moves and shots played, for the wandering wumpus
+    turns: u32,
     pub moves_count: u32,
     pub start_time: f64,
     pub end_time: f64,
+    seed: u64,
+    rng: GameRng,
+    clock: Arc<dyn Clock>,
+    // Set in practice games, which can take actions back but never score
+    practice: Option<Practice>,
 }
 
 impl GameModel {
     pub fn new() -> Self {
-        let tunnels = GameModel::create_cave_topology();
-        let room_positions = GameModel::calculate_room_positions();
+        GameModel::from_seed(rand::rng().random())
+    }
+
+    /// Creates a game that can be replayed exactly: the same seed always
+    /// yields the same cave layout and the same random outcomes.
+    pub fn from_seed(seed: u64) -> Self {
+        GameModel::with_seed_and_clock(seed, Arc::new(SystemClock::new()))
+    }
+
+    /// Creates a seeded game whose timings come from the given clock instead
+    /// of the wall clock.
+    pub fn with_seed_and_clock(seed: u64, clock: Arc<dyn Clock>) -> Self {
+        let cave = CaveTopology::dodecahedron();
+        let layout = LayoutStrategy::default_for(cave.kind());
+        let room_positions = GameModel::calculate_room_positions(&cave, layout);
         let mut model = GameModel {
-            tunnels,
+            cave,
             player_position: 0,
             wumpus_position: 0,
             bat_positions: HashSet::new(),
             pit_positions: HashSet::new(),
             game_over: false,
             win: false,
-            has_arrow: true,
-            message: String::from(
-                "Benvenuto a Hunt the Wumpus! Usa le frecce per muoverti, spazio per tirare una freccia.",
-            ),
+            difficulty: Difficulty::default(),
+            arrows: Difficulty::default().rules().arrows,
+            message: vec![Message::new("welcome")],
+            layout,
             room_positions,
+            map: CaveMap::default(),
+            log: Vec::new(),
+            turns: 0,
             moves_count: 0,
             start_time: 0.0,
             end_time: 0.0,
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+            clock,
+            practice: None,
         };
         model.initialize_game();
         model
     }
 
-    fn create_cave_topology() -> [[usize; NUM_TUNNELS_PER_ROOM]; NUM_ROOMS] {
-        [
-            [1, 4, 5],    // 0
-            [0, 2, 7],    // 1
-            [1, 3, 9],    // 2
-            [2, 4, 11],   // 3
-            [0, 3, 13],   // 4
-            [6, 14, 0],   // 5
-            [5, 7, 18],   // 6
-            [6, 8, 1],    // 7
-            [7, 9, 19],   // 8
-            [8, 10, 2],   // 9
-            [9, 11, 15],  // 10
-            [10, 12, 3],  // 11
-            [11, 13, 16], // 12
-            [12, 14, 4],  // 13
-            [5, 13, 17],  // 14
-            [16, 19, 10], // 15
-            [15, 17, 12], // 16
-            [16, 18, 14], // 17
-            [17, 19, 6],  // 18
-            [15, 18, 8],  // 19
-        ]
-    }
-
-    fn calculate_node_positions_by_ring(
-        center: (f32, f32),
-        radius: f32,
-        num_nodes: usize,
-        start_angle_rad: f32,
-    ) -> Vec<Vec2> {
-        let mut positions = Vec::with_capacity(num_nodes);
-        if num_nodes == 0 {
-            return positions;
-        }
-
-        let angle_step = 2.0 * PI / num_nodes as f32;
-
-        for i in 0..num_nodes {
-            let current_angle = start_angle_rad + (i as f32) * angle_step;
-            let node_x = center.0 + radius * current_angle.cos();
-            let node_y = center.1 + radius * current_angle.sin();
-            positions.push(Vec2::new(node_x, node_y));
-        }
-
-        positions
-    }
-
-    fn calculate_room_positions() -> Vec<Vec2> {
-        let center_x = SCREEN_WIDTH / 2.0;
-        let center_y = SCREEN_HEIGHT / 2.0;
-
-        (0..NUM_RINGS)
-            .flat_map(|i| {
-                GameModel::calculate_node_positions_by_ring(
-                    (center_x, center_y),
-                    RING_RADII[i],
-                    NODES_PER_RING[i],
-                    START_ANGLES[i],
-                )
-            })
-            .collect()
+    fn calculate_room_positions(cave: &CaveTopology, layout: LayoutStrategy) -> Vec<Vec2> {
+        let area = LayoutArea {
+            center: Vec2::new(SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0),
+            radius: RING_RADII[0],
+        };
+        layout::compute_layout(cave, layout, area)
     }
 
     fn initialize_game(&mut self) {
-        let mut rng = rng();
-        let mut available_rooms: Vec<usize> = (0..NUM_ROOMS).collect();
-        available_rooms.shuffle(&mut rng);
+        self.cave = CaveTopology::generate(self.cave.kind(), &mut self.rng);
+        self.room_positions = GameModel::calculate_room_positions(&self.cave, self.layout);
+
+        let mut available_rooms: Vec<usize> = (0..self.cave.num_rooms()).collect();
+        available_rooms.shuffle(&mut self.rng);
 
         self.player_position = available_rooms.pop().unwrap();
         self.wumpus_position = available_rooms.pop().unwrap();
 
+        let rules = self.difficulty.rules();
+
         self.bat_positions.clear();
-        for _ in 0..NUM_BATS {
+        for _ in 0..rules.bats {
             self.bat_positions.insert(available_rooms.pop().unwrap());
         }
 
         self.pit_positions.clear();
-        for _ in 0..NUM_PITS {
+        for _ in 0..rules.pits {
             self.pit_positions.insert(available_rooms.pop().unwrap());
         }
 
         self.game_over = false;
         self.win = false;
-        self.has_arrow = true;
-        self.message = String::from(
-            "Benvenuto a Hunt the Wumpus! Usa le frecce per muoverti, spazio per tirare una freccia.",
-        );
+        self.arrows = rules.arrows;
+        self.turns = 0;
+        self.message = vec![Message::new("welcome")];
         self.moves_count = 0;
-        self.start_time = get_time();
+        self.start_time = self.clock.now();
         self.end_time = 0.0;
+
+        self.map = CaveMap::new(self.cave.num_rooms());
+        if let Some(practice) = &mut self.practice {
+            *practice = Practice::default();
+        }
+
+        let mut bats: Vec<usize> = self.bat_positions.iter().copied().collect();
+        let mut pits: Vec<usize> = self.pit_positions.iter().copied().collect();
+        bats.sort_unstable();
+        pits.sort_unstable();
+        self.log.clear();
+        self.record(GameEvent::GameStarted {
+            seed: self.seed,
+            cave: self.cave.kind(),
+            difficulty: self.difficulty,
+            player: self.player_position,
+            wumpus: self.wumpus_position,
+            bats,
+            pits,
+        });
+        self.visit_current_room();
+    }
+
+    fn record(&mut self, event: GameEvent) {
+        self.log.push(LogEntry {
+            time: self.clock.now() - self.start_time,
+            event,
+        });
+    }
+
+    /// Everything that happened since the game started, in order.
+    pub fn log(&self) -> &[LogEntry] {
+        &self.log
     }
 
-    pub fn move_player(&mut self, room: usize) -> bool {
-        if !self.tunnels[self.player_position].contains(&room) {
-            self.message = format!("Non puoi andare direttamente alla stanza {}!", room);
-            return false;
+    // Events recorded since the log had `start` entries
+    fn events_since(&self, start: usize) -> Vec<GameEvent> {
+        self.log[start..]
+            .iter()
+            .map(|entry| entry.event.clone())
+            .collect()
+    }
+
+    /// Plays one of the player's actions: the single entry point through
+    /// which front-ends, replays and bots change the game. Returns what
+    /// happened, or why the action was refused.
+    ///
+    /// In practice games the state before every accepted action is kept,
+    /// so it can be taken back with [`GameModel::undo`].
+    pub fn apply(&mut self, action: Action) -> Result<Vec<GameEvent>, GameError> {
+        let before = self.snapshot();
+        let result = match action {
+            Action::Move(room) => self.move_player(room),
+            Action::Shoot(path) => self.shoot_arrow(&path),
+        };
+        if result.is_ok()
+            && let (Some(practice), Some(before)) = (&mut self.practice, before)
+        {
+            practice.undo.push(before);
+            practice.redo.clear();
         }
+        result
+    }
 
-        self.player_position = room;
+    // Copy of the game without its practice history, in practice games only
+    fn snapshot(&mut self) -> Option<GameModel> {
+        let practice = self.practice.take()?;
+        let snapshot = self.clone();
+        self.practice = Some(practice);
+        Some(snapshot)
+    }
+
+    /// Whether this is a practice game, where actions can be taken back and
+    /// nothing counts towards scores or statistics.
+    pub fn is_practice(&self) -> bool {
+        self.practice.is_some()
+    }
 
-        if self.player_position == self.wumpus_position {
-            self.message = String::from("Sei stato mangiato dal Wumpus! Game Over!");
-            self.game_over = true;
-            return true;
+    /// Turns practice on or off and starts a new game with the current seed,
+    /// so a game cannot change from one kind to the other halfway through.
+    pub fn set_practice(&mut self, practice: bool) {
+        self.practice = practice.then(Practice::default);
+        self.restart();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.practice
+            .as_ref()
+            .is_some_and(|practice| !practice.undo.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.practice
+            .as_ref()
+            .is_some_and(|practice| !practice.redo.is_empty())
+    }
+
+    /// Takes back the last action of a practice game, even one that ended
+    /// it.
+    pub fn undo(&mut self) -> Result<(), GameError> {
+        let practice = self.practice.as_mut().ok_or(GameError::NotPractice)?;
+        let previous = practice.undo.pop().ok_or(GameError::NothingToUndo)?;
+        let current = self.restore(previous, "undo.done");
+        if let Some(practice) = &mut self.practice {
+            practice.redo.push(current);
         }
+        Ok(())
+    }
 
-        if self.pit_positions.contains(&self.player_position) {
-            self.message = String::from("Sei caduto in una fossa! Game Over!");
-            self.game_over = true;
-            return true;
+    /// Plays again the last action taken back with [`GameModel::undo`].
+    pub fn redo(&mut self) -> Result<(), GameError> {
+        let practice = self.practice.as_mut().ok_or(GameError::NotPractice)?;
+        let next = practice.redo.pop().ok_or(GameError::NothingToRedo)?;
+        let current = self.restore(next, "redo.done");
+        if let Some(practice) = &mut self.practice {
+            practice.undo.push(current);
         }
+        Ok(())
+    }
 
-        if self.bat_positions.contains(&self.player_position) {
-            let mut rng = rng();
-            self.player_position = rng.random_range(0..NUM_ROOMS);
-            self.message = format!(
-                "Sei stato trasportato da un pipistrello gigante alla stanza {}!",
-                self.player_position
-            );
+    // Swaps in a snapshot, keeping the practice history, and returns the
+    // state it replaced
+    fn restore(&mut self, snapshot: GameModel, key: &str) -> GameModel {
+        let practice = self.practice.take();
+        let current = std::mem::replace(self, snapshot);
+        self.practice = practice;
+        if !self.game_over {
+            self.generate_warnings();
+        }
+        self.message.insert(0, Message::new(key));
+        current
+    }
+
+    /// Walks the player through the tunnel to `room`. Returns everything
+    /// that happened, starting with [`GameEvent::Moved`], and describes it
+    /// in the message shown to the player.
+    pub fn move_player(&mut self, room: usize) -> Result<Vec<GameEvent>, GameError> {
+        if self.game_over {
+            return Err(GameError::GameAlreadyOver);
+        }
+        if room >= self.cave.num_rooms() {
+            return Err(GameError::RoomOutOfRange(room));
+        }
+        if !self.cave.is_tunnel(self.player_position, room) {
+            return Err(GameError::NotAdjacent(room));
+        }
 
-            if self.player_position == self.wumpus_position {
-                self.message =
-                    String::from("Sei stato trasportato nella stanza del Wumpus! Game Over!");
-                self.game_over = true;
-                return true;
+        let start = self.log.len();
+        self.walk(room);
+        self.end_turn();
+        // The player looks around once the wumpus has wandered, so the clues
+        // tell where it is now
+        if !self.game_over {
+            self.visit_current_room();
+        }
+        let events = self.events_since(start);
+        self.message = events::describe(&events);
+        Ok(events)
+    }
+
+    fn walk(&mut self, room: usize) {
+        self.record(GameEvent::Moved {
+            from: self.player_position,
+            to: room,
+        });
+        self.turns += 1;
+        self.moves_count += 1;
+        self.player_position = room;
+
+        // A bat may drop the player in another bat's room, so the player
+        // is carried until landing somewhere the bats leave alone
+        loop {
+            let room = self.player_position;
+
+            if room == self.wumpus_position {
+                self.end_game(false);
+                self.record(GameEvent::EatenByWumpus { room });
+                break;
+            }
+
+            if self.pit_positions.contains(&room) {
+                self.end_game(false);
+                self.record(GameEvent::FellInPit { room });
+                break;
             }
 
-            if self.pit_positions.contains(&self.player_position) {
-                self.message =
-                    String::from("Sei stato trasportato in una stanza con una fossa! Game Over!");
-                self.game_over = true;
-                return true;
+            if !self.bat_positions.contains(&room) {
+                break;
             }
 
-            return self.move_player(self.player_position);
+            self.carry_off();
         }
+    }
 
-        self.generate_warnings();
+    // The bat in the player's room drops them in another room, chosen by
+    // the drop rules of the difficulty, then flies off if the difficulty
+    // says so
+    fn carry_off(&mut self) {
+        let from = self.player_position;
+        let rules = self.difficulty.rules();
+        let safe = rules.safe_drop;
+        let rooms: Vec<usize> = (0..self.cave.num_rooms())
+            .filter(|&room| {
+                room != from
+                    && !(safe
+                        && (room == self.wumpus_position || self.pit_positions.contains(&room)))
+            })
+            .collect();
+        let to = *rooms.choose(&mut self.rng).unwrap();
+
+        self.player_position = to;
+        self.record(GameEvent::CarriedByBats { from, to });
+        if rules.bats_relocate {
+            self.relocate_bat(from);
+        }
+    }
+
+    /// Rooms the bats flew the player through since the last action,
+    /// starting where they were snatched; empty if no bat was met.
+    pub fn bat_flight(&self) -> Vec<usize> {
+        let mut flight = Vec::new();
+        for entry in self.log.iter().rev() {
+            match entry.event {
+                GameEvent::CarriedByBats { from, to } => {
+                    if flight.is_empty() {
+                        flight.push(to);
+                    }
+                    flight.push(from);
+                }
+                ref event if event.is_player_action() => break,
+                _ => {}
+            }
+        }
+        flight.reverse();
+        flight
+    }
 
-        true
+    /// What can be sensed from `room`: hazards in the rooms its tunnels
+    /// lead to.
+    pub fn sense(&self, room: usize) -> Clues {
+        let exits = self.cave.exits(room);
+        Clues {
+            stench: exits.contains(&self.wumpus_position),
+            breeze: exits
+                .iter()
+                .any(|tunnel| self.pit_positions.contains(tunnel)),
+            squeak: exits
+                .iter()
+                .any(|tunnel| self.bat_positions.contains(tunnel)),
+        }
+    }
+
+    /// Rooms the player knows about, indexed by room: the visited ones and
+    /// those their tunnels lead to. Fog of war hides all the others.
+    pub fn known_rooms(&self) -> Vec<bool> {
+        let mut known = vec![false; self.cave.num_rooms()];
+        for room in self.map.visited_rooms() {
+            known[room] = true;
+            for &exit in self.cave.exits(room) {
+                known[exit] = true;
+            }
+        }
+        known
+    }
+
+    /// The exits of `room` that lead to the hazards sensed there.
+    pub fn clue_sources(&self, room: usize) -> ClueSources {
+        let exits = self.cave.exits(room).iter().copied();
+        ClueSources {
+            stench: exits
+                .clone()
+                .filter(|&exit| exit == self.wumpus_position)
+                .collect(),
+            breeze: exits
+                .clone()
+                .filter(|exit| self.pit_positions.contains(exit))
+                .collect(),
+            squeak: exits
+                .filter(|exit| self.bat_positions.contains(exit))
+                .collect(),
+        }
+    }
+
+    // Marks the player's room as visited and records the clues sensed there
+    fn visit_current_room(&mut self) {
+        let room = self.player_position;
+        let clues = self.sense(room);
+        self.map.visit(room, clues);
+        if clues.stench {
+            self.record(GameEvent::SensedStench { room });
+        }
+        if clues.breeze {
+            self.record(GameEvent::SensedBreeze { room });
+        }
+        if clues.squeak {
+            self.record(GameEvent::SensedBats { room });
+        }
     }
 
     pub fn generate_warnings(&mut self) {
-        let mut warnings = Vec::new();
+        self.set_message(Message::new("room.current").arg("room", self.player_position));
+
+        let clues = self.sense(self.player_position);
+
+        if clues.stench {
+            self.message.push(Message::new("warning.stench"));
+        }
+
+        if clues.breeze {
+            self.message.push(Message::new("warning.breeze"));
+        }
 
-        if self.tunnels[self.player_position].contains(&self.wumpus_position) {
-            warnings.push("Senti un fetore nauseabondo...");
+        if clues.squeak {
+            self.message.push(Message::new("warning.squeak"));
         }
 
-        for &tunnel in &self.tunnels[self.player_position] {
-            if self.pit_positions.contains(&tunnel) {
-                warnings.push("Senti una brezza leggera...");
+        if clues.is_empty() {
+            self.message.push(Message::new("room.quiet"));
+        }
+    }
+
+    /// Replaces the feedback shown to the player.
+    pub fn set_message(&mut self, message: Message) {
+        self.message = vec![message];
+    }
+
+    /// Shoots a crooked arrow along `path`, a list of up to
+    /// [`MAX_ARROW_PATH`] rooms starting next to the player.
+    ///
+    /// Each step follows the requested room when a tunnel leads there;
+    /// otherwise the arrow is deflected into a random tunnel. The arrow kills
+    /// the wumpus as soon as it enters its room and hits the player if it
+    /// flies back into theirs.
+    ///
+    /// Returns everything that happened, starting with
+    /// [`GameEvent::ArrowShot`], and describes it in the message. A path the
+    /// arrow cannot take is refused without wasting an arrow.
+    pub fn shoot_arrow(&mut self, path: &[usize]) -> Result<Vec<GameEvent>, GameError> {
+        if self.game_over {
+            return Err(GameError::GameAlreadyOver);
+        }
+        if self.arrows == 0 {
+            return Err(GameError::NoArrows);
+        }
+        if path.is_empty() || path.len() > MAX_ARROW_PATH {
+            return Err(GameError::PathTooLong);
+        }
+        if let Some(&room) = path.iter().find(|&&room| room >= self.cave.num_rooms()) {
+            return Err(GameError::RoomOutOfRange(room));
+        }
+        if path.windows(3).any(|w| w[0] == w[2]) {
+            return Err(GameError::PathTooCrooked);
+        }
+
+        let start = self.log.len();
+        self.fire(path);
+        let events = self.events_since(start);
+        self.message = events::describe(&events);
+        Ok(events)
+    }
+
+    fn fire(&mut self, path: &[usize]) {
+        self.arrows -= 1;
+        self.turns += 1;
+        let wumpus = self.wumpus_position;
+
+        let mut arrow_position = self.player_position;
+        let mut flight = Vec::with_capacity(path.len());
+        for &next_room in path {
+            arrow_position = if self.cave.is_tunnel(arrow_position, next_room) {
+                next_room
+            } else {
+                *self
+                    .cave
+                    .exits(arrow_position)
+                    .choose(&mut self.rng)
+                    .unwrap()
+            };
+            flight.push(arrow_position);
+
+            if arrow_position == self.wumpus_position || arrow_position == self.player_position {
                 break;
             }
         }
+        self.record(GameEvent::ArrowShot {
+            path: path.to_vec(),
+            flight: flight.clone(),
+        });
 
-        for &tunnel in &self.tunnels[self.player_position] {
-            if self.bat_positions.contains(&tunnel) {
-                warnings.push("Senti uno squittio in lontananza...");
-                break;
+        if arrow_position == self.wumpus_position {
+            self.end_game(true);
+            self.record(GameEvent::WumpusKilled {
+                room: arrow_position,
+            });
+            return;
+        }
+
+        if arrow_position == self.player_position {
+            self.end_game(false);
+            self.record(GameEvent::ShotSelf {
+                room: arrow_position,
+            });
+            return;
+        }
+
+        self.record(GameEvent::ArrowMissed);
+
+        if self.rng.random::<f32>() < self.difficulty.rules().wake_chance {
+            let wumpus_tunnels = self.cave.exits(self.wumpus_position);
+            let new_wumpus_pos = *wumpus_tunnels.choose(&mut self.rng).unwrap();
+            self.record(GameEvent::WumpusWoke {
+                from: self.wumpus_position,
+                to: new_wumpus_pos,
+            });
+            self.wumpus_position = new_wumpus_pos;
+
+            if new_wumpus_pos == self.player_position {
+                self.end_game(false);
+                self.record(GameEvent::EatenByWumpus {
+                    room: self.player_position,
+                });
+                return;
             }
         }
 
-        if warnings.is_empty() {
-            self.message = format!(
-                "Ti trovi nella stanza {}. Tutto sembra tranquillo.",
-                self.player_position
-            );
-        } else {
-            self.message = format!(
-                "Ti trovi nella stanza {}. {}",
-                self.player_position,
-                warnings.join(" ")
-            );
+        if self.arrows == 0 {
+            self.end_game(false);
+            self.record(GameEvent::OutOfArrows);
+            return;
+        }
+
+        self.end_turn();
+        // The wumpus woke or wandered, so the clues around the player changed
+        if !self.game_over && self.wumpus_position != wumpus {
+            self.visit_current_room();
         }
     }
 
-    pub fn shoot_arrow(&mut self, target_room: usize) {
-        if !self.has_arrow {
-            self.message = String::from("Non hai più frecce!");
-            return;
+    // Stops the game and its clock
+    fn end_game(&mut self, win: bool) {
+        self.game_over = true;
+        self.win = win;
+        self.end_time = self.clock.now();
+    }
+
+    /// How the game ended, or `None` while it is still going. Games loaded
+    /// from saves without a log only tell whether they were won.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.game_over {
+            return None;
+        }
+        if self.win {
+            return Some(Outcome::Won);
         }
+        self.log.iter().rev().find_map(|entry| match entry.event {
+            GameEvent::EatenByWumpus { .. } => Some(Outcome::EatenByWumpus),
+            GameEvent::FellInPit { .. } => Some(Outcome::FellInPit),
+            GameEvent::ShotSelf { .. } => Some(Outcome::ShotSelf),
+            GameEvent::OutOfArrows => Some(Outcome::OutOfArrows),
+            _ => None,
+        })
+    }
 
-        if !self.tunnels[self.player_position].contains(&target_room) {
-            self.message = format!("Non puoi tirare la freccia alla stanza {}!", target_room);
+    pub fn stats(&self) -> GameStats {
+        GameStats {
+            moves: self.moves_count,
+            shots: self.difficulty.rules().arrows.saturating_sub(self.arrows),
+            bat_rides: self
+                .log
+                .iter()
+                .filter(|entry| matches!(entry.event, GameEvent::CarriedByBats { .. }))
+                .count() as u32,
+            rooms_explored: self.map.visited_rooms().count() as u32,
+            time: self.elapsed_time(),
+        }
+    }
+
+    // On harder difficulties the wumpus wanders on its own every few turns
+    fn end_turn(&mut self) {
+        let Some(every) = self.difficulty.rules().wander_every else {
+            return;
+        };
+        if self.game_over || !self.turns.is_multiple_of(every) {
             return;
         }
 
-        self.has_arrow = false;
+        let from = self.wumpus_position;
+        let to = *self.cave.exits(from).choose(&mut self.rng).unwrap();
+        self.wumpus_position = to;
+        self.record(GameEvent::WumpusWandered { from, to });
+
+        if to == self.player_position {
+            self.end_game(false);
+            self.record(GameEvent::EatenByWumpus { room: to });
+        }
+    }
+
+    // The bat that carried the player off settles in another empty room
+    fn relocate_bat(&mut self, from: usize) {
+        let empty: Vec<usize> = (0..self.cave.num_rooms())
+            .filter(|room| {
+                *room != self.player_position
+                    && *room != self.wumpus_position
+                    && !self.bat_positions.contains(room)
+                    && !self.pit_positions.contains(room)
+            })
+            .collect();
+        if let Some(&to) = empty.choose(&mut self.rng) {
+            self.bat_positions.remove(&from);
+            self.bat_positions.insert(to);
+            self.record(GameEvent::BatsMoved { from, to });
+        }
+    }
+
+    /// Switches to a different kind of cave and starts a new game in it with
+    /// the current seed.
+    pub fn set_cave_kind(&mut self, kind: CaveKind) {
+        self.cave = CaveTopology::generate(kind, &mut self.rng);
+        self.layout = LayoutStrategy::default_for(kind);
+        self.restart();
+    }
+
+    /// Switches to another difficulty and starts a new game with the
+    /// current seed.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.restart();
+    }
+
+    /// Redraws the map of the current cave with another layout strategy.
+    pub fn set_layout(&mut self, layout: LayoutStrategy) {
+        self.layout = layout;
+        self.room_positions = GameModel::calculate_room_positions(&self.cave, layout);
+    }
+
+    /// Seed of the game in progress.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts a new game. The next seed is drawn from the current generator,
+    /// so a whole session started from a known seed stays reproducible.
+    pub fn reset(&mut self) {
+        let seed = self.rng.random();
+        self.reset_with_seed(seed);
+    }
 
-        if target_room == self.wumpus_position {
-            self.message = String::from("Hai colpito il Wumpus! Hai vinto!");
-            self.game_over = true;
-            self.win = true;
-            self.end_time = get_time();
+    /// Replays the current game from the beginning.
+    pub fn restart(&mut self) {
+        self.reset_with_seed(self.seed);
+    }
+
+    pub fn reset_with_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = GameRng::seed_from_u64(seed);
+        self.initialize_game();
+    }
+
+    /// Seconds played so far, frozen once the game is over.
+    pub fn elapsed_time(&self) -> f64 {
+        if self.game_over {
+            self.end_time - self.start_time
         } else {
-            self.message = format!(
-                "Hai mancato! La freccia è andata nella stanza {}.",
-                target_room
-            );
-
-            let mut rng = rng();
-            if rng.random::<f32>() < 0.75 {
-                let wumpus_tunnels = &self.tunnels[self.wumpus_position];
-                let new_wumpus_pos = *wumpus_tunnels.choose(&mut rng).unwrap();
-
-                if new_wumpus_pos == self.player_position {
-                    self.message =
-                        String::from("Il Wumpus si è svegliato e ti ha trovato! Game Over!");
-                    self.game_over = true;
-                    self.end_time = get_time();
-                } else {
-                    self.wumpus_position = new_wumpus_pos;
-                    self.message += " Il Wumpus si è svegliato e si è spostato!";
+            self.clock.now() - self.start_time
+        }
+    }
+
+    /// Restarts the game clock, for a game that waited before being played,
+    /// e.g. behind the title screen or the menus. Once the player has acted,
+    /// the clock keeps running.
+    pub fn start_clock(&mut self) {
+        if self.turns == 0 && !self.game_over {
+            self.start_time = self.clock.now();
+        }
+    }
+
+    /// Captures the whole game, hidden state included, for saving.
+    pub fn to_save(&self) -> SaveGame {
+        let mut bat_positions: Vec<usize> = self.bat_positions.iter().copied().collect();
+        let mut pit_positions: Vec<usize> = self.pit_positions.iter().copied().collect();
+        bat_positions.sort_unstable();
+        pit_positions.sort_unstable();
+
+        SaveGame {
+            version: SAVE_VERSION,
+            seed: self.seed,
+            cave: self.cave.clone(),
+            layout: self.layout,
+            player_position: self.player_position,
+            wumpus_position: self.wumpus_position,
+            bat_positions,
+            pit_positions,
+            arrows: self.arrows,
+            moves_count: self.moves_count,
+            elapsed_time: self.elapsed_time(),
+            game_over: self.game_over,
+            win: self.win,
+            difficulty: self.difficulty,
+            turns: self.turns,
+            message: self.message.clone(),
+            map: self.map.clone(),
+            log: self.log.clone(),
+            rng: self.rng.clone(),
+            practice: self.is_practice(),
+        }
+    }
+
+    /// Rebuilds a game from a save, checking that it describes a playable
+    /// cave. Time keeps running from the elapsed time stored in the save.
+    pub fn from_save(save: SaveGame, clock: Arc<dyn Clock>) -> Result<Self, SaveError> {
+        if save.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(save.version));
+        }
+        if !save.cave.is_valid() {
+            return Err(SaveError::Corrupted("caverna non valida"));
+        }
+
+        let num_rooms = save.cave.num_rooms();
+        let in_cave = |room: &usize| *room < num_rooms;
+        if !in_cave(&save.player_position)
+            || !in_cave(&save.wumpus_position)
+            || !save.bat_positions.iter().all(in_cave)
+            || !save.pit_positions.iter().all(in_cave)
+        {
+            return Err(SaveError::Corrupted("stanza fuori dalla caverna"));
+        }
+        if !save.map.fits(num_rooms) {
+            return Err(SaveError::Corrupted("mappa di dimensioni errate"));
+        }
+
+        let now = clock.now();
+        let start_time = now - save.elapsed_time;
+        Ok(GameModel {
+            room_positions: GameModel::calculate_room_positions(&save.cave, save.layout),
+            cave: save.cave,
+            player_position: save.player_position,
+            wumpus_position: save.wumpus_position,
+            bat_positions: save.bat_positions.into_iter().collect(),
+            pit_positions: save.pit_positions.into_iter().collect(),
+            game_over: save.game_over,
+            win: save.win,
+            difficulty: save.difficulty,
+            arrows: save.arrows,
+            message: save.message,
+            layout: save.layout,
+            map: save.map,
+            log: save.log,
+            turns: save.turns,
+            moves_count: save.moves_count,
+            start_time,
+            end_time: if save.game_over { now } else { 0.0 },
+            seed: save.seed,
+            rng: save.rng,
+            clock,
+            practice: save.practice.then(Practice::default),
+        })
+    }
+}
+
+impl Default for GameModel {
+    fn default() -> Self {
+        GameModel::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{self, Agent, Percept};
+    use crate::clock::ManualClock;
+    use crate::solver::Solver;
+
+    // A seeded game on a still clock, so that even the log times repeat
+    fn game(seed: u64) -> GameModel {
+        GameModel::with_seed_and_clock(seed, Arc::new(ManualClock::new(0.0)))
+    }
+
+    // Lets the solver play `model` to the end
+    fn play_out(model: &mut GameModel) {
+        let mut solver = Solver::new(model.difficulty);
+        agent::play(model, &mut solver, 1000);
+        assert!(model.game_over);
+    }
+
+    // Everything a save keeps, as JSON
+    fn state(model: &GameModel) -> String {
+        model.to_save().to_json().unwrap()
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        for seed in 0..20 {
+            let mut first = game(seed);
+            let mut second = game(seed);
+            assert_eq!(state(&first), state(&second));
+
+            play_out(&mut first);
+            play_out(&mut second);
+            assert_eq!(first.log(), second.log());
+            assert_eq!(state(&first), state(&second));
+        }
+    }
+
+    #[test]
+    fn different_seeds_different_caves() {
+        let hidden = |model: &GameModel| {
+            let save = model.to_save();
+            (save.wumpus_position, save.bat_positions, save.pit_positions)
+        };
+        let caves = (0..20)
+            .map(|seed| hidden(&game(seed)))
+            .collect::<HashSet<_>>();
+        assert!(caves.len() > 1);
+    }
+
+    #[test]
+    fn illegal_moves_are_refused() {
+        let mut model = game(1);
+        let before = state(&model);
+        let room = model.player_position;
+        let far = (0..model.cave.num_rooms())
+            .find(|&other| other != room && !model.cave.is_tunnel(room, other))
+            .unwrap();
+        let outside = model.cave.num_rooms();
+
+        assert_eq!(
+            model.apply(Action::Move(far)),
+            Err(GameError::NotAdjacent(far))
+        );
+        assert_eq!(
+            model.apply(Action::Move(outside)),
+            Err(GameError::RoomOutOfRange(outside))
+        );
+        assert_eq!(state(&model), before);
+    }
+
+    #[test]
+    fn illegal_shots_are_refused() {
+        let mut model = game(1);
+        let before = state(&model);
+        let exit = model.cave.exits(model.player_position)[0];
+        let outside = model.cave.num_rooms();
+
+        assert_eq!(
+            model.apply(Action::Shoot(vec![])),
+            Err(GameError::PathTooLong)
+        );
+        assert_eq!(
+            model.apply(Action::Shoot(vec![exit; MAX_ARROW_PATH + 1])),
+            Err(GameError::PathTooLong)
+        );
+        assert_eq!(
+            model.apply(Action::Shoot(vec![exit, outside])),
+            Err(GameError::RoomOutOfRange(outside))
+        );
+        assert_eq!(
+            model.apply(Action::Shoot(vec![exit, model.player_position, exit])),
+            Err(GameError::PathTooCrooked)
+        );
+        assert_eq!(state(&model), before);
+
+        model.arrows = 0;
+        assert_eq!(
+            model.apply(Action::Shoot(vec![exit])),
+            Err(GameError::NoArrows)
+        );
+    }
+
+    #[test]
+    fn nothing_is_played_after_game_over() {
+        let mut model = game(1);
+        play_out(&mut model);
+        let before = state(&model);
+        let exit = model.cave.exits(model.player_position)[0];
+
+        assert_eq!(
+            model.apply(Action::Move(exit)),
+            Err(GameError::GameAlreadyOver)
+        );
+        assert_eq!(
+            model.apply(Action::Shoot(vec![exit])),
+            Err(GameError::GameAlreadyOver)
+        );
+        assert_eq!(state(&model), before);
+    }
+
+    // A practice game, and its state leaving out the feedback message, which
+    // tells about the undo itself
+    fn practice(seed: u64) -> GameModel {
+        let mut model = game(seed);
+        model.set_practice(true);
+        model
+    }
+
+    fn quiet_state(model: &GameModel) -> String {
+        let mut save = model.to_save();
+        save.message.clear();
+        save.to_json().unwrap()
+    }
+
+    #[test]
+    fn undo_and_redo_restore_snapshots() {
+        let mut model = practice(1);
+        let start = quiet_state(&model);
+        let exit = model.cave.exits(model.player_position)[0];
+        model.apply(Action::Move(exit)).unwrap();
+        let moved = quiet_state(&model);
+
+        assert_eq!(model.undo(), Ok(()));
+        assert_eq!(quiet_state(&model), start);
+        assert_eq!(model.undo(), Err(GameError::NothingToUndo));
+
+        assert_eq!(model.redo(), Ok(()));
+        assert_eq!(quiet_state(&model), moved);
+        assert_eq!(model.redo(), Err(GameError::NothingToRedo));
+    }
+
+    #[test]
+    fn a_new_action_forgets_what_was_undone() {
+        let mut model = practice(1);
+        let exits = model.cave.exits(model.player_position).to_vec();
+        model.apply(Action::Move(exits[0])).unwrap();
+        model.undo().unwrap();
+        assert!(model.can_redo());
+
+        model.apply(Action::Move(exits[1])).unwrap();
+        assert!(!model.can_redo());
+        assert_eq!(model.redo(), Err(GameError::NothingToRedo));
+    }
+
+    #[test]
+    fn refused_actions_are_not_undoable() {
+        let mut model = practice(1);
+        let outside = model.cave.num_rooms();
+        assert!(model.apply(Action::Move(outside)).is_err());
+        assert!(!model.can_undo());
+    }
+
+    #[test]
+    fn the_last_action_of_a_game_can_be_undone() {
+        let mut model = practice(1);
+        play_out(&mut model);
+        model.undo().unwrap();
+        assert!(!model.game_over);
+        assert_eq!(model.outcome(), None);
+    }
+
+    #[test]
+    fn scored_games_cannot_undo() {
+        let mut model = game(1);
+        let exit = model.cave.exits(model.player_position)[0];
+        model.apply(Action::Move(exit)).unwrap();
+        let before = state(&model);
+
+        assert!(!model.can_undo());
+        assert_eq!(model.undo(), Err(GameError::NotPractice));
+        assert_eq!(model.redo(), Err(GameError::NotPractice));
+        assert_eq!(state(&model), before);
+    }
+
+    #[test]
+    fn time_before_playing_does_not_count() {
+        let clock = Arc::new(ManualClock::new(100.0));
+        let mut model = GameModel::with_seed_and_clock(1, clock.clone());
+        clock.advance(30.0);
+        model.start_clock();
+        assert_eq!(model.elapsed_time(), 0.0);
+
+        let exit = model.cave.exits(model.player_position)[0];
+        model.apply(Action::Move(exit)).unwrap();
+        clock.advance(5.0);
+        assert_eq!(model.elapsed_time(), 5.0);
+
+        // A game under way keeps its time
+        model.start_clock();
+        assert_eq!(model.elapsed_time(), 5.0);
+    }
+
+    // A game on `difficulty` where the player walks straight into a bat's
+    // room, with what happened
+    fn into_bats(difficulty: Difficulty, seed: u64) -> (GameModel, Vec<GameEvent>) {
+        let mut model = game(seed);
+        model.set_difficulty(difficulty);
+        let bat = *model.bat_positions.iter().min().unwrap();
+        model.player_position = model.cave.exits(bat)[0];
+        let events = model.apply(Action::Move(bat)).unwrap();
+        (model, events)
+    }
+
+    fn carries(events: &[GameEvent]) -> Vec<(usize, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                GameEvent::CarriedByBats { from, to } => Some((from, to)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bats_drop_the_player_elsewhere() {
+        for difficulty in Difficulty::ALL {
+            for seed in 0..50 {
+                let (model, events) = into_bats(difficulty, seed);
+                let carries = carries(&events);
+                assert!(!carries.is_empty());
+                assert!(carries.iter().all(|(from, to)| from != to));
+
+                // The player lands safely, or the game ends where they land,
+                // instead of being refused the move
+                let landed = carries.last().unwrap().1;
+                assert!(model.player_position == landed || model.game_over);
+                assert!(
+                    model
+                        .message
+                        .iter()
+                        .all(|message| message.key != "move.not_adjacent")
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn safe_drops_avoid_hazards() {
+        assert!(Difficulty::Easy.rules().safe_drop);
+        for seed in 0..200 {
+            let (model, events) = into_bats(Difficulty::Easy, seed);
+            for (_, to) in carries(&events) {
+                assert_ne!(to, model.wumpus_position);
+                assert!(!model.pit_positions.contains(&to));
+            }
+            assert!(!model.game_over);
+        }
+    }
+
+    #[test]
+    fn bats_fly_off_to_empty_rooms_where_the_rules_say_so() {
+        for difficulty in Difficulty::ALL {
+            let rules = difficulty.rules();
+            for seed in 0..50 {
+                let (model, events) = into_bats(difficulty, seed);
+                let moved: Vec<(usize, usize)> = events
+                    .iter()
+                    .filter_map(|event| match *event {
+                        GameEvent::BatsMoved { from, to } => Some((from, to)),
+                        _ => None,
+                    })
+                    .collect();
+                assert_eq!(model.bat_positions.len(), rules.bats);
+                if !rules.bats_relocate {
+                    assert!(moved.is_empty());
+                    continue;
+                }
+                assert_eq!(moved.len(), carries(&events).len());
+                for (from, to) in moved {
+                    assert_ne!(from, to);
+                    assert_ne!(to, model.wumpus_position);
+                    assert!(!model.pit_positions.contains(&to));
                 }
             }
         }
     }
 
-    pub fn reset(&mut self) {
-        self.initialize_game();
+    // The rooms an arrow flew through, from the events of a shot
+    fn flight(events: &[GameEvent]) -> Vec<usize> {
+        match &events[0] {
+            GameEvent::ArrowShot { flight, .. } => flight.clone(),
+            event => panic!("not a shot: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn arrows_follow_tunnels_or_deflect() {
+        let mut deflected = 0;
+        for seed in 0..100 {
+            let mut model = game(seed);
+            let start = model.player_position;
+            let exit = model.cave.exits(start)[0];
+            // A room no tunnel from the first one leads to
+            let far = (0..model.cave.num_rooms())
+                .find(|&room| room != start && !model.cave.is_tunnel(exit, room))
+                .unwrap();
+            let path = [exit, far];
+
+            let events = model.apply(Action::Shoot(path.to_vec())).unwrap();
+            let flight = flight(&events);
+            let mut from = start;
+            for (&asked, &flown) in path.iter().zip(&flight) {
+                assert!(model.cave.is_tunnel(from, flown));
+                if model.cave.is_tunnel(from, asked) {
+                    assert_eq!(flown, asked);
+                } else if flown != asked {
+                    deflected += 1;
+                }
+                from = flown;
+            }
+        }
+        assert!(deflected > 0);
+    }
+
+    #[test]
+    fn arrows_can_come_back_to_the_archer() {
+        let mut model = game(1);
+        let start = model.player_position;
+        let exit = model.cave.exits(start)[0];
+        model.wumpus_position = (0..model.cave.num_rooms())
+            .find(|&room| room != start && room != exit)
+            .unwrap();
+
+        let events = model.apply(Action::Shoot(vec![exit, start])).unwrap();
+        assert_eq!(flight(&events), [exit, start]);
+        assert!(events.contains(&GameEvent::ShotSelf { room: start }));
+        assert_eq!(model.outcome(), Some(Outcome::ShotSelf));
+    }
+
+    #[test]
+    fn arrows_stop_at_the_wumpus() {
+        let mut model = game(1);
+        let start = model.player_position;
+        let exit = model.cave.exits(start)[0];
+        model.wumpus_position = exit;
+
+        let events = model.apply(Action::Shoot(vec![exit, start])).unwrap();
+        assert_eq!(flight(&events), [exit]);
+        assert_eq!(model.outcome(), Some(Outcome::Won));
+    }
+
+    #[test]
+    fn known_rooms_are_the_visited_ones_and_their_exits() {
+        for seed in 0..20 {
+            let mut model = game(seed);
+            play_out(&mut model);
+
+            for (room, known) in model.known_rooms().into_iter().enumerate() {
+                let seen = model.map.is_visited(room)
+                    || model
+                        .map
+                        .visited_rooms()
+                        .any(|visited| model.cave.exits(visited).contains(&room));
+                assert_eq!(known, seen, "seed {} room {}", seed, room);
+            }
+        }
+    }
+
+    #[test]
+    fn clues_are_sensed_after_the_wumpus_moves() {
+        for difficulty in [Difficulty::Hard, Difficulty::Nightmare] {
+            for seed in 0..50 {
+                let mut model = game(seed);
+                model.set_difficulty(difficulty);
+                let mut solver = Solver::new(difficulty);
+                let mut percept = Percept::observe(&model, false);
+                while !model.game_over {
+                    let action = solver.act(&percept);
+                    let moved = matches!(action, Action::Move(_));
+                    let bump = model.apply(action).is_err();
+                    percept = Percept::observe(&model, bump);
+                    if model.game_over || bump {
+                        continue;
+                    }
+
+                    // The map and the message agree with the room as it is now
+                    let room = model.player_position;
+                    let clues = model.sense(room);
+                    assert_eq!(model.map.clues(room), Some(clues));
+                    let warned = model.message.iter().any(|m| m.key == "warning.stench");
+                    if moved {
+                        assert_eq!(warned, clues.stench);
+                    }
+                }
+            }
+        }
     }
 }
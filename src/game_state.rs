@@ -1,6 +1,43 @@
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameState {
-    Splash, // Schermata iniziale
-    Play,   // Gioco in corso
-    Over,   // Fine partita (vittoria o sconfitta)
+    Splash,      // Schermata iniziale
+    Play,        // Gioco in corso
+    Over,        // Fine partita (vittoria o sconfitta)
+    Replay,      // Rivedi la partita appena giocata
+    Stats,       // Statistiche del giocatore
+    EnterName,   // Nome del giocatore per un nuovo record
+    Leaderboard, // Tabelle dei punteggi migliori
+    Settings,    // Impostazioni del gioco
+}
+
+// Voci della schermata delle impostazioni, nell'ordine in cui appaiono
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Setting {
+    Language,
+    Difficulty,
+    Practice,
+    Cave,
+    Layout,
+    Volume,
+    Sound,
+    Colorblind,
+    WindowSize,
+    Fullscreen,
+    AnimationSpeed,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 11] = [
+        Setting::Language,
+        Setting::Difficulty,
+        Setting::Practice,
+        Setting::Cave,
+        Setting::Layout,
+        Setting::Volume,
+        Setting::Sound,
+        Setting::Colorblind,
+        Setting::WindowSize,
+        Setting::Fullscreen,
+        Setting::AnimationSpeed,
+    ];
 }
@@ -1,33 +1,162 @@
-use crate::game_model::GameModel;
 use macroquad::prelude::*;
+use rustedbytes_wumpus::layout::{RING_RADII, SCREEN_HEIGHT, SCREEN_WIDTH};
+use rustedbytes_wumpus::{
+    CaveKind, Date, Difficulty, GameModel, HighScore, Language, LayoutStrategy, Marker, Message,
+    Profile, Replay, Settings, Translations, leaderboard,
+};
+
+use crate::game_state::Setting;
 
 // Constants
-const SCREEN_WIDTH: f32 = 800.0;
-const SCREEN_HEIGHT: f32 = 600.0;
 const ROOM_RADIUS: f32 = 12.0;
-pub const RING_RADII: [f32; 3] = [200.0, 140.0, 60.0];
 pub const LINE_THICKNESS: f32 = 2.0;
 
+// Seconds a bat takes to fly the player from one room to the next, at the
+// normal animation speed
+const BAT_HOP_TIME: f64 = 0.6;
+
+/// Colors of the player, the dangers and the clues on the map.
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub player: Color,
+    pub danger: Color,
+    pub stench: Color,
+    pub breeze: Color,
+    pub squeak: Color,
+}
+
+impl Palette {
+    pub const STANDARD: Palette = Palette {
+        player: GREEN,
+        danger: RED,
+        stench: BROWN,
+        breeze: SKYBLUE,
+        squeak: PURPLE,
+    };
+
+    /// Okabe-Ito colors, told apart also by players who confuse red and
+    /// green.
+    pub const COLORBLIND: Palette = Palette {
+        player: Color::new(0.0, 0.45, 0.7, 1.0),
+        danger: Color::new(0.84, 0.37, 0.0, 1.0),
+        stench: Color::new(0.9, 0.62, 0.0, 1.0),
+        breeze: Color::new(0.34, 0.71, 0.91, 1.0),
+        squeak: Color::new(0.8, 0.47, 0.65, 1.0),
+    };
+}
+
+/// Camera scaling the virtual screen to the window. The whole virtual screen
+/// stays visible and centred; when the window has other proportions the
+/// extra room on the sides (or above and below) shows the background.
+pub fn screen_camera() -> Camera2D {
+    let scale = (screen_width() / SCREEN_WIDTH).min(screen_height() / SCREEN_HEIGHT);
+    let (width, height) = (screen_width() / scale, screen_height() / scale);
+    Camera2D {
+        target: vec2(SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0),
+        // Unlike `Camera2D::from_display_rect` this keeps y growing downwards
+        // as in screen coordinates
+        zoom: vec2(2.0 / width, 2.0 / height),
+        ..Default::default()
+    }
+}
+
+/// Position of the mouse on the virtual screen.
+pub fn virtual_mouse_position() -> Vec2 {
+    screen_camera().screen_to_world(mouse_position().into())
+}
+
+// Text rasterized at the resolution of the window rather than of the
+// virtual screen, so that it stays sharp however much the camera scales it
+fn draw_text(text: &str, x: f32, y: f32, font_size: f32, color: Color) {
+    let (font_size, font_scale, font_scale_aspect) = camera_font_scale(font_size);
+    draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font_size,
+            font_scale,
+            font_scale_aspect,
+            color,
+            ..Default::default()
+        },
+    );
+}
+
+// A bat carrying the player through `rooms`, animated since `start`
+struct BatFlight {
+    rooms: Vec<usize>,
+    start: f64,
+}
+
 pub struct GameView {
+    pub fog_of_war: bool,
+    pub translations: Translations,
+    pub palette: Palette,
+    /// Multiple of the normal speed of the animations.
+    pub animation_speed: f32,
     font_size: f32,
     title_font_size: f32,
     splash_texture: Option<Texture2D>,
+    bat_flight: Option<BatFlight>,
 }
 
 impl GameView {
     pub fn new() -> Self {
         GameView {
+            fog_of_war: true,
+            translations: Translations::new(Language::FALLBACK),
+            palette: Palette::STANDARD,
+            animation_speed: 1.0,
             font_size: 20.0,
             title_font_size: 40.0,
             splash_texture: None,
+            bat_flight: None,
         }
     }
 
+    // Translated text of a message key
+    fn tr(&self, key: &'static str) -> &str {
+        self.translations.get(key)
+    }
+
+    fn text(&self, message: &Message) -> String {
+        self.translations.format(message)
+    }
+
+    fn bat_hop_time(&self) -> f64 {
+        BAT_HOP_TIME / self.animation_speed as f64
+    }
+
+    /// Starts animating a bat carrying the player through `rooms`, as given
+    /// by [`GameModel::bat_flight`].
+    pub fn start_bat_flight(&mut self, rooms: Vec<usize>) {
+        self.bat_flight = Some(BatFlight {
+            rooms,
+            start: get_time(),
+        });
+    }
+
+    /// Whether a bat is still flying the player to where they were dropped.
+    pub fn is_flying(&self) -> bool {
+        self.bat_flight.as_ref().is_some_and(|flight| {
+            get_time() - flight.start < self.bat_hop_time() * (flight.rooms.len() - 1) as f64
+        })
+    }
+
     pub async fn load_resources(&mut self) {
         self.splash_texture = load_texture("assets/splash.png").await.ok();
     }
 
-    pub fn draw_splash(&self) {
+    pub fn draw_splash(
+        &self,
+        cave: CaveKind,
+        layout: LayoutStrategy,
+        difficulty: Difficulty,
+        practice: bool,
+        can_continue: bool,
+        (today, daily_played): (Date, bool),
+    ) {
         clear_background(BLACK);
 
         if let Some(splash_texture) = &self.splash_texture {
@@ -49,7 +178,7 @@ impl GameView {
             GOLD,
         );
 
-        let subtitle = "Un classico gioco di avventura";
+        let subtitle = self.tr("splash.subtitle");
         let subtitle_size = measure_text(subtitle, None, (self.font_size * 1.2) as u16, 1.0);
         draw_text(
             subtitle,
@@ -60,70 +189,200 @@ impl GameView {
         );
 
         let instructions = [
-            "Esplora le stanze della caverna e caccia il temibile Wumpus.",
-            "Attenzione alle fosse e ai pipistrelli giganti!",
-            "Usa il mouse per muoverti tra le stanze.",
-            "Premi SPAZIO per attivare la modalità di tiro con l'arco.",
-            "",
-            "Premi SPAZIO o clicca per iniziare",
-        ];
+            "splash.help1",
+            "splash.help2",
+            "splash.help3",
+            "splash.help4",
+            "splash.help5",
+        ]
+        .map(|key| self.tr(key));
 
         for (i, line) in instructions.iter().enumerate() {
             let line_size = measure_text(line, None, self.font_size as u16, 1.0);
             draw_text(
                 line,
                 SCREEN_WIDTH / 2.0 - line_size.width / 2.0,
-                SCREEN_HEIGHT / 2.0 + (i as f32 * 30.0),
+                SCREEN_HEIGHT / 2.0 - 10.0 + (i as f32 * 24.0),
                 self.font_size,
                 LIGHTGRAY,
             );
         }
 
+        // Options of the next game, changed with the keys shown beside them
+        let options = [
+            Message::new("splash.cave").arg("cave", cave.key()),
+            Message::new("splash.layout").arg("layout", layout.key()),
+            Message::new(if practice {
+                "splash.practice"
+            } else {
+                "splash.difficulty"
+            })
+            .arg("difficulty", difficulty.key()),
+            Message::new("splash.bats").arg("bats", difficulty.rules().bats_key()),
+            Message::new("splash.language").arg("language", self.translations.language().name()),
+            Message::new("splash.stats"),
+            Message::new(if daily_played {
+                "splash.daily_done"
+            } else {
+                "splash.daily"
+            })
+            .arg("date", today),
+        ];
+        for (i, option) in options.iter().enumerate() {
+            let option_text = self.text(option);
+            let option_size = measure_text(&option_text, None, self.font_size as u16, 1.0);
+            draw_text(
+                &option_text,
+                SCREEN_WIDTH / 2.0 - option_size.width / 2.0,
+                SCREEN_HEIGHT / 2.0 + 105.0 + (i as f32 * 20.0),
+                self.font_size,
+                GOLD,
+            );
+        }
+
         let blink_rate = ((get_time() * 2.0) as f32).sin() * 0.5 + 0.5;
-        let press_start = "Premi SPAZIO o clicca per iniziare";
+        let press_start = self.tr("splash.start");
         let press_start_size = measure_text(press_start, None, self.font_size as u16, 1.0);
         draw_text(
             press_start,
             SCREEN_WIDTH / 2.0 - press_start_size.width / 2.0,
-            SCREEN_HEIGHT - 100.0,
+            SCREEN_HEIGHT - 60.0,
             self.font_size,
             Color::new(1.0, 1.0, 1.0, blink_rate),
         );
+
+        if can_continue {
+            let continue_text = self.tr("splash.continue");
+            let continue_size = measure_text(continue_text, None, self.font_size as u16, 1.0);
+            draw_text(
+                continue_text,
+                SCREEN_WIDTH / 2.0 - continue_size.width / 2.0,
+                SCREEN_HEIGHT - 35.0,
+                self.font_size,
+                GOLD,
+            );
+        }
     }
 
     pub fn draw_game(&self, model: &GameModel) {
+        // Until the game ends, in explored mode only the visited rooms and
+        // the exits seen from them are drawn
+        self.draw_board(
+            model,
+            self.fog_of_war && !model.game_over,
+            self.tr("play.help"),
+        );
+
+        if self.is_flying() {
+            self.draw_bat_flight(model);
+            return;
+        }
+
+        if model.game_over {
+            let end_message = self.tr(if model.win { "play.won" } else { "play.lost" });
+
+            let text_size = measure_text(end_message, None, (self.font_size * 1.5) as u16, 1.0);
+            draw_rectangle(
+                SCREEN_WIDTH / 2.0 - text_size.width / 2.0 - 20.0,
+                SCREEN_HEIGHT / 2.0 - 30.0,
+                text_size.width + 40.0,
+                60.0,
+                Color::new(0.0, 0.0, 0.0, 0.8),
+            );
+            draw_text(
+                end_message,
+                SCREEN_WIDTH / 2.0 - text_size.width / 2.0,
+                SCREEN_HEIGHT / 2.0 + 10.0,
+                self.font_size * 1.5,
+                if model.win {
+                    self.palette.player
+                } else {
+                    self.palette.danger
+                },
+            );
+        }
+    }
+
+    // Map, messages and HUD of the play screen; with `fog` only the rooms the
+    // player knows about are drawn
+    fn draw_board(&self, model: &GameModel, fog: bool, help: &str) {
         clear_background(WHITE);
 
         let center_x = SCREEN_WIDTH / 2.0;
         let center_y = SCREEN_HEIGHT / 2.0;
         let center = (center_x, center_y);
 
-        for &radius in RING_RADII.iter() {
-            draw_poly_lines(
-                center.0,
-                center.1,
-                200,
-                radius,
-                0.0,
-                LINE_THICKNESS,
-                LIGHTGRAY,
-            );
-        }
+        let known = model.known_rooms();
 
-        for i in 0..model.tunnels.len() {
-            let base = model.room_positions[i];
-            let conn = model.tunnels[i][2];
-            if i < conn {
-                let other = model.room_positions[conn];
-                draw_line(base.x, base.y, other.x, other.y, LINE_THICKNESS, LIGHTGRAY);
+        if fog {
+            for (from, to) in model.cave.edges() {
+                if model.map.is_visited(from)
+                    || (!model.cave.is_directed() && model.map.is_visited(to))
+                {
+                    self.draw_tunnel(
+                        model.room_positions[from],
+                        model.room_positions[to],
+                        model.cave.is_directed(),
+                    );
+                }
+            }
+        } else if model.cave.kind() == CaveKind::Dodecahedron
+            && model.layout == LayoutStrategy::ConcentricRings
+        {
+            // The rings of the dodecahedron are drawn as circles, only the
+            // spokes between them as straight lines
+            for &radius in RING_RADII.iter() {
+                draw_poly_lines(
+                    center.0,
+                    center.1,
+                    200,
+                    radius,
+                    0.0,
+                    LINE_THICKNESS,
+                    LIGHTGRAY,
+                );
+            }
+
+            for i in 0..model.cave.num_rooms() {
+                let base = model.room_positions[i];
+                let conn = model.cave.exits(i)[2];
+                if i < conn {
+                    let other = model.room_positions[conn];
+                    draw_line(base.x, base.y, other.x, other.y, LINE_THICKNESS, LIGHTGRAY);
+                }
+            }
+        } else {
+            for (from, to) in model.cave.edges() {
+                self.draw_tunnel(
+                    model.room_positions[from],
+                    model.room_positions[to],
+                    model.cave.is_directed(),
+                );
             }
         }
 
         for (i, pos) in model.room_positions.iter().enumerate() {
-            let color = if i == model.player_position {
-                GREEN
+            if fog && !known[i] {
+                continue;
+            }
+
+            if fog && !model.map.is_visited(i) {
+                draw_circle_lines(pos.x, pos.y, ROOM_RADIUS, LINE_THICKNESS, GRAY);
+                draw_text(
+                    &i.to_string(),
+                    pos.x - 10.0,
+                    pos.y + 8.0,
+                    self.font_size,
+                    BLUE,
+                );
+                self.draw_room_notes(model, i);
+                continue;
+            }
+
+            let color = if i == model.player_position && !self.is_flying() {
+                self.palette.player
             } else if model.game_over && i == model.wumpus_position {
-                RED
+                self.palette.danger
             } else {
                 GRAY
             };
@@ -136,64 +395,304 @@ impl GameView {
                 self.font_size,
                 BLUE,
             );
+            self.draw_room_notes(model, i);
         }
 
-        let message_width = measure_text(&model.message, None, self.font_size as u16, 1.0).width;
+        let message = self.translations.format_all(&model.message);
+        let message_width = measure_text(&message, None, self.font_size as u16, 1.0).width;
         draw_text(
-            &model.message,
+            &message,
             SCREEN_WIDTH / 2.0 - message_width / 2.0,
             SCREEN_HEIGHT - 50.0,
             self.font_size,
             BLACK,
         );
 
-        let arrow_text = if model.has_arrow {
-            "Freccia: [x]"
-        } else {
-            "Freccia: [ ]"
-        };
-        draw_text(arrow_text, 20.0, 30.0, self.font_size, BLUE);
+        let quiver = format!(
+            "{}{}",
+            "x".repeat(model.arrows as usize),
+            " ".repeat(model.difficulty.rules().arrows.saturating_sub(model.arrows) as usize)
+        );
+        let arrow_text = self.text(&Message::new("hud.arrows").arg("arrows", quiver));
+        draw_text(&arrow_text, 20.0, 30.0, self.font_size, BLUE);
 
+        let seed_text = self.text(&Message::new("hud.seed").arg("seed", model.seed()));
+        let seed_width = measure_text(&seed_text, None, self.font_size as u16, 1.0).width;
         draw_text(
-            "Usa il mouse per muoverti o tirare frecce",
+            &seed_text,
+            SCREEN_WIDTH - seed_width - 20.0,
+            30.0,
+            self.font_size,
+            GRAY,
+        );
+        let difficulty_text = self.text(
+            &Message::new("hud.difficulty")
+                .arg("difficulty", model.difficulty.key())
+                .arg("bats", model.difficulty.rules().bats_key()),
+        );
+        let difficulty_width =
+            measure_text(&difficulty_text, None, self.font_size as u16, 1.0).width;
+        draw_text(
+            &difficulty_text,
+            SCREEN_WIDTH - difficulty_width - 20.0,
+            55.0,
+            self.font_size,
+            GRAY,
+        );
+        // Practice games are flagged for as long as they last
+        if model.is_practice() {
+            let practice_text = self.tr("hud.practice");
+            let practice_width =
+                measure_text(practice_text, None, self.font_size as u16, 1.0).width;
+            draw_text(
+                practice_text,
+                SCREEN_WIDTH - practice_width - 20.0,
+                80.0,
+                self.font_size,
+                ORANGE,
+            );
+        }
+
+        self.draw_clue_legend();
+
+        draw_text(
+            help,
             20.0,
             SCREEN_HEIGHT - 20.0,
             self.font_size - 5.0,
             LIGHTGRAY,
         );
+    }
 
-        if model.game_over {
-            let end_message = if model.win {
-                "Hai vinto! Premi R per ricominciare."
-            } else {
-                "Game Over! Premi R per ricominciare."
-            };
+    /// Draws a recorded game at the current step, with every hazard in view
+    /// and the events of the last action listed on the left.
+    pub fn draw_replay(&self, replay: &Replay) {
+        let model = replay.model();
+        self.draw_board(model, false, self.tr("replay.help"));
 
-            let text_size = measure_text(end_message, None, (self.font_size * 1.5) as u16, 1.0);
-            draw_rectangle(
-                SCREEN_WIDTH / 2.0 - text_size.width / 2.0 - 20.0,
-                SCREEN_HEIGHT / 2.0 - 30.0,
-                text_size.width + 40.0,
-                60.0,
-                Color::new(0.0, 0.0, 0.0, 0.8),
+        let (bats, pits) = replay.hazards();
+        let hazards = bats
+            .iter()
+            .map(|&room| (room, "hazard.bats", self.palette.squeak))
+            .chain(
+                pits.iter()
+                    .map(|&room| (room, "hazard.pit", self.palette.breeze)),
+            )
+            .chain(std::iter::once((
+                model.wumpus_position,
+                "hazard.wumpus",
+                self.palette.danger,
+            )));
+        for (room, label, color) in hazards {
+            let pos = model.room_positions[room];
+            draw_circle_lines(pos.x, pos.y, ROOM_RADIUS + 4.0, LINE_THICKNESS, color);
+            draw_text(
+                self.tr(label),
+                pos.x + ROOM_RADIUS + 2.0,
+                pos.y - ROOM_RADIUS,
+                self.font_size - 4.0,
+                color,
+            );
+        }
+
+        let title = self.text(
+            &Message::new("replay.title")
+                .arg("step", replay.step())
+                .arg("total", replay.len()),
+        );
+        let title_width = measure_text(&title, None, self.font_size as u16, 1.0).width;
+        draw_text(
+            &title,
+            SCREEN_WIDTH / 2.0 - title_width / 2.0,
+            30.0,
+            self.font_size,
+            DARKGRAY,
+        );
+
+        for (i, entry) in replay.current_events().iter().enumerate() {
+            draw_text(
+                &format!(
+                    "{:>6.1}s  {}",
+                    entry.time,
+                    self.text(&entry.event.message())
+                ),
+                20.0,
+                140.0 + i as f32 * 18.0,
+                self.font_size - 4.0,
+                DARKGRAY,
             );
+        }
+    }
+
+    // Clues remembered on a visited room, or the player's markers on an
+    // unexplored one
+    fn draw_room_notes(&self, model: &GameModel, room: usize) {
+        let pos = model.room_positions[room];
+
+        if let Some(clues) = model.map.clues(room) {
+            let dots = [
+                (clues.stench, self.palette.stench),
+                (clues.breeze, self.palette.breeze),
+                (clues.squeak, self.palette.squeak),
+            ];
+            for (i, (_, color)) in dots.iter().filter(|(sensed, _)| *sensed).enumerate() {
+                draw_circle(
+                    pos.x - ROOM_RADIUS + i as f32 * 8.0,
+                    pos.y + ROOM_RADIUS + 6.0,
+                    3.5,
+                    *color,
+                );
+            }
+            return;
+        }
+
+        let label: String = model
+            .map
+            .markers(room)
+            .iter()
+            .map(|marker| {
+                self.tr(match marker {
+                    Marker::Pit => "marker.pit",
+                    Marker::Bats => "marker.bats",
+                    Marker::Wumpus => "marker.wumpus",
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !label.is_empty() {
             draw_text(
-                end_message,
-                SCREEN_WIDTH / 2.0 - text_size.width / 2.0,
-                SCREEN_HEIGHT / 2.0 + 10.0,
-                self.font_size * 1.5,
-                if model.win { GREEN } else { RED },
+                &label,
+                pos.x - ROOM_RADIUS,
+                pos.y + ROOM_RADIUS + 14.0,
+                self.font_size - 4.0,
+                RED,
             );
         }
     }
 
+    // Legend of the clue colors
+    fn draw_clue_legend(&self) {
+        let entries = [
+            ("clue.stench", self.palette.stench),
+            ("clue.breeze", self.palette.breeze),
+            ("clue.squeak", self.palette.squeak),
+        ];
+        let mut x = 20.0;
+        let y = 80.0;
+        for (key, color) in entries {
+            let label = self.tr(key);
+            draw_circle(x + 4.0, y - 5.0, 4.0, color);
+            draw_text(label, x + 12.0, y, self.font_size - 4.0, DARKGRAY);
+            x += 12.0 + measure_text(label, None, (self.font_size - 4.0) as u16, 1.0).width + 12.0;
+        }
+    }
+
+    /// Labels the selectable exits with their number-key shortcut and rings
+    /// the one focused by keyboard or gamepad.
+    pub fn draw_exit_hints(&self, model: &GameModel, exits: &[usize], focused: Option<usize>) {
+        if model.game_over {
+            return;
+        }
+
+        let known = model.known_rooms();
+        for (i, &room) in exits.iter().enumerate() {
+            if self.fog_of_war && !known[room] {
+                continue;
+            }
+
+            let pos = model.room_positions[room];
+            draw_text(
+                &(i + 1).to_string(),
+                pos.x + ROOM_RADIUS,
+                pos.y - ROOM_RADIUS,
+                self.font_size - 4.0,
+                DARKGREEN,
+            );
+            if focused == Some(room) {
+                draw_circle_lines(pos.x, pos.y, ROOM_RADIUS + 2.0, LINE_THICKNESS, DARKGREEN);
+            }
+        }
+    }
+
+    /// Draws the crooked arrow path being built in shoot mode, starting from
+    /// the player's room.
+    pub fn draw_arrow_path(&self, model: &GameModel, path: &[usize]) {
+        let mut from = model.room_positions[model.player_position];
+        for &room in path {
+            let to = model.room_positions[room];
+            draw_line(from.x, from.y, to.x, to.y, LINE_THICKNESS * 2.0, ORANGE);
+            draw_circle_lines(to.x, to.y, ROOM_RADIUS + 4.0, LINE_THICKNESS, ORANGE);
+            from = to;
+        }
+
+        let rooms = path
+            .iter()
+            .map(|room| room.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let path_text = self.text(&Message::new("hud.arrow_path").arg("rooms", rooms));
+        draw_text(&path_text, 20.0, 55.0, self.font_size, ORANGE);
+    }
+
+    // The rooms flown over so far and the bat with the player, eased from
+    // one room to the next
+    fn draw_bat_flight(&self, model: &GameModel) {
+        let Some(flight) = &self.bat_flight else {
+            return;
+        };
+        let hops = (get_time() - flight.start) / self.bat_hop_time();
+        let hop = (hops as usize).min(flight.rooms.len() - 2);
+        let t = (hops - hop as f64).clamp(0.0, 1.0) as f32;
+        let t = t * t * (3.0 - 2.0 * t);
+
+        for pair in flight.rooms[..=hop + 1].windows(2) {
+            let from = model.room_positions[pair[0]];
+            let to = model.room_positions[pair[1]];
+            draw_line(from.x, from.y, to.x, to.y, 1.0, self.palette.squeak);
+        }
+
+        let from = model.room_positions[flight.rooms[hop]];
+        let to = model.room_positions[flight.rooms[hop + 1]];
+        let pos = from.lerp(to, t);
+        // The wings flap a few times per hop
+        let flap = (get_time() * 16.0).sin() as f32 * 6.0;
+
+        draw_circle(pos.x, pos.y + 10.0, ROOM_RADIUS * 0.6, self.palette.player);
+        draw_triangle(
+            pos,
+            vec2(pos.x - 22.0, pos.y - 8.0 + flap),
+            vec2(pos.x - 10.0, pos.y + 4.0),
+            BLACK,
+        );
+        draw_triangle(
+            pos,
+            vec2(pos.x + 22.0, pos.y - 8.0 + flap),
+            vec2(pos.x + 10.0, pos.y + 4.0),
+            BLACK,
+        );
+        draw_circle(pos.x, pos.y, 5.0, BLACK);
+    }
+
+    fn draw_tunnel(&self, from: Vec2, to: Vec2, directed: bool) {
+        draw_line(from.x, from.y, to.x, to.y, LINE_THICKNESS, LIGHTGRAY);
+
+        if directed {
+            // Arrow head just outside the destination room
+            let dir = (to - from).normalize_or_zero();
+            let tip = to - dir * ROOM_RADIUS;
+            let side = Vec2::new(-dir.y, dir.x) * 5.0;
+            let back = tip - dir * 10.0;
+            draw_triangle(tip, back + side, back - side, GRAY);
+        }
+    }
+
     pub fn draw_game_over(&self, model: &GameModel, transition_timer: f32) {
         self.draw_game(model);
 
         let alpha = f32::min(transition_timer, 1.0);
 
         let wumpus_pos = model.room_positions[model.wumpus_position];
-        draw_circle(wumpus_pos.x, wumpus_pos.y, ROOM_RADIUS, RED);
+        draw_circle(wumpus_pos.x, wumpus_pos.y, ROOM_RADIUS, self.palette.danger);
 
         draw_rectangle(
             0.0,
@@ -203,11 +702,7 @@ impl GameView {
             Color::new(0.0, 0.0, 0.0, 0.8 * alpha),
         );
 
-        let end_message = if model.win {
-            "Hai vinto! Hai eliminato il Wumpus!"
-        } else {
-            "Game Over! Il Wumpus ti ha preso!"
-        };
+        let end_message = self.tr(if model.win { "over.won" } else { "over.lost" });
 
         let text_size = measure_text(end_message, None, (self.title_font_size * 0.8) as u16, 1.0);
         draw_text(
@@ -215,38 +710,389 @@ impl GameView {
             SCREEN_WIDTH / 2.0 - text_size.width / 2.0,
             SCREEN_HEIGHT / 2.0 - 50.0,
             self.title_font_size * 0.8,
-            if model.win { GREEN } else { RED },
+            if model.win {
+                self.palette.player
+            } else {
+                self.palette.danger
+            },
         );
 
-        let game_time = model.end_time - model.start_time;
-        let stats_message = format!(
-            "Mosse: {} | Tempo: {:.1} secondi",
-            model.moves_count, game_time
-        );
+        let stats = model.stats();
+        let mut stats_lines = vec![
+            stats.message(),
+            Message::new("over.time")
+                .arg("time", format!("{:.1}", stats.time))
+                .arg("seed", model.seed()),
+        ];
+        if let Some(score) = leaderboard::score(model) {
+            stats_lines.push(Message::new("over.score").arg("score", score));
+        }
+        if model.is_practice() {
+            stats_lines.push(Message::new("over.practice"));
+        }
+        for (i, line) in stats_lines.iter().enumerate() {
+            let stats_message = self.text(line);
+            let stats_size = measure_text(&stats_message, None, self.font_size as u16, 1.0);
+            draw_text(
+                &stats_message,
+                SCREEN_WIDTH / 2.0 - stats_size.width / 2.0,
+                SCREEN_HEIGHT / 2.0 - 15.0 + i as f32 * 22.0,
+                self.font_size,
+                WHITE,
+            );
+        }
 
-        let stats_size = measure_text(&stats_message, None, self.font_size as u16, 1.0);
+        let restart_text = self.tr("over.restart");
+        let restart_size = measure_text(restart_text, None, self.font_size as u16, 1.0);
         draw_text(
-            &stats_message,
-            SCREEN_WIDTH / 2.0 - stats_size.width / 2.0,
-            SCREEN_HEIGHT / 2.0,
+            restart_text,
+            SCREEN_WIDTH / 2.0 - restart_size.width / 2.0,
+            SCREEN_HEIGHT / 2.0 + 55.0,
             self.font_size,
             WHITE,
         );
 
-        let restart_text = "Premi R per ricominciare o ESC per tornare al menu";
-        let restart_size = measure_text(restart_text, None, self.font_size as u16, 1.0);
+        let replay_text = self.tr("over.replay");
+        let replay_size = measure_text(replay_text, None, self.font_size as u16, 1.0);
         draw_text(
-            restart_text,
-            SCREEN_WIDTH / 2.0 - restart_size.width / 2.0,
-            SCREEN_HEIGHT / 2.0 + 50.0,
+            replay_text,
+            SCREEN_WIDTH / 2.0 - replay_size.width / 2.0,
+            SCREEN_HEIGHT / 2.0 + 85.0,
+            self.font_size,
+            LIGHTGRAY,
+        );
+    }
+
+    /// Result of the daily cave, under the game over banner.
+    pub fn draw_daily_result(&self, result: &str) {
+        // The list of moves is left for the clipboard, only the summary
+        // fits on the screen
+        let summary = result.split(" [").next().unwrap_or(result);
+        let lines = [
+            (
+                self.text(&Message::new("daily.result").arg("result", summary)),
+                GOLD,
+            ),
+            (self.tr("daily.copy").to_string(), LIGHTGRAY),
+        ];
+        for (i, (text, color)) in lines.iter().enumerate() {
+            let width = measure_text(text, None, self.font_size as u16, 1.0).width;
+            draw_text(
+                text,
+                SCREEN_WIDTH / 2.0 - width / 2.0,
+                SCREEN_HEIGHT / 2.0 + 115.0 + i as f32 * 25.0,
+                self.font_size,
+                *color,
+            );
+        }
+    }
+
+    /// Box asking the name of a player who made the leaderboard, drawn over
+    /// the game over screen.
+    pub fn draw_name_entry(&self, model: &GameModel, name: &str) {
+        let (width, height) = (420.0, 170.0);
+        let (x, y) = ((SCREEN_WIDTH - width) / 2.0, (SCREEN_HEIGHT - height) / 2.0);
+        draw_rectangle(x, y, width, height, BLACK);
+        draw_rectangle_lines(x, y, width, height, LINE_THICKNESS, GOLD);
+
+        let title = self
+            .text(&Message::new("name.title").arg("score", leaderboard::score(model).unwrap_or(0)));
+        // The cursor blinks after the name typed so far
+        let cursor = if ((get_time() * 2.0) as u64).is_multiple_of(2) {
+            "_"
+        } else {
+            " "
+        };
+        let name = format!("{}{}", name, cursor);
+        let lines = [
+            (title.as_str(), self.title_font_size * 0.8, GOLD),
+            (self.tr("name.prompt"), self.font_size, WHITE),
+            (name.as_str(), self.font_size * 1.5, GREEN),
+            (self.tr("name.help"), self.font_size, GRAY),
+        ];
+        for (i, (text, size, color)) in lines.into_iter().enumerate() {
+            let text_width = measure_text(text, None, size as u16, 1.0).width;
+            draw_text(
+                text,
+                SCREEN_WIDTH / 2.0 - text_width / 2.0,
+                y + 40.0 + i as f32 * 36.0,
+                size,
+                color,
+            );
+        }
+    }
+
+    /// One table of the leaderboard; `highlight` is the rank of the entry
+    /// just added.
+    pub fn draw_leaderboard(
+        &self,
+        table: &[&HighScore],
+        (difficulty, cave): (Difficulty, CaveKind),
+        highlight: Option<usize>,
+    ) {
+        clear_background(BLACK);
+
+        let title = self.tr("leaderboard.title");
+        let title_size = measure_text(title, None, self.title_font_size as u16, 1.0);
+        draw_text(
+            title,
+            SCREEN_WIDTH / 2.0 - title_size.width / 2.0,
+            70.0,
+            self.title_font_size,
+            GOLD,
+        );
+
+        let subtitle = self.text(
+            &Message::new("leaderboard.table")
+                .arg("difficulty", difficulty.key())
+                .arg("cave", cave.key()),
+        );
+        let subtitle_size = measure_text(&subtitle, None, self.font_size as u16, 1.0);
+        draw_text(
+            &subtitle,
+            SCREEN_WIDTH / 2.0 - subtitle_size.width / 2.0,
+            110.0,
             self.font_size,
             WHITE,
         );
+
+        // Left edge of each column: rank, name, score, moves, time, seed
+        let columns = [40.0, 80.0, 290.0, 380.0, 460.0, 550.0];
+        let header = [
+            "#",
+            self.tr("leaderboard.name"),
+            self.tr("leaderboard.score"),
+            self.tr("leaderboard.moves"),
+            self.tr("leaderboard.time"),
+            self.tr("leaderboard.seed"),
+        ];
+        for (x, text) in columns.iter().zip(header) {
+            draw_text(text, *x, 160.0, self.font_size, GRAY);
+        }
+
+        if table.is_empty() {
+            let empty = self.tr("leaderboard.empty");
+            let empty_size = measure_text(empty, None, self.font_size as u16, 1.0);
+            draw_text(
+                empty,
+                SCREEN_WIDTH / 2.0 - empty_size.width / 2.0,
+                220.0,
+                self.font_size,
+                LIGHTGRAY,
+            );
+        }
+
+        for (rank, entry) in table.iter().enumerate() {
+            let y = 195.0 + rank as f32 * 30.0;
+            let color = if highlight == Some(rank) {
+                GREEN
+            } else {
+                WHITE
+            };
+            let cells = [
+                (rank + 1).to_string(),
+                entry.name.clone(),
+                entry.score.to_string(),
+                entry.moves.to_string(),
+                format!("{:.1}", entry.time),
+                entry.seed.to_string(),
+            ];
+            for (x, text) in columns.iter().zip(cells) {
+                draw_text(&text, *x, y, self.font_size, color);
+            }
+        }
+
+        let help = self.tr("leaderboard.help");
+        let help_size = measure_text(help, None, self.font_size as u16, 1.0);
+        draw_text(
+            help,
+            SCREEN_WIDTH / 2.0 - help_size.width / 2.0,
+            SCREEN_HEIGHT - 30.0,
+            self.font_size,
+            GRAY,
+        );
+    }
+
+    /// Screen with the record of every game played.
+    pub fn draw_stats(&self, profile: &Profile) {
+        clear_background(BLACK);
+
+        let title = self.tr("stats.title");
+        let title_size = measure_text(title, None, self.title_font_size as u16, 1.0);
+        draw_text(
+            title,
+            SCREEN_WIDTH / 2.0 - title_size.width / 2.0,
+            80.0,
+            self.title_font_size,
+            GOLD,
+        );
+
+        let losses = &profile.losses;
+        let mut lines = vec![
+            (
+                Message::new("stats.games")
+                    .arg("games", profile.games)
+                    .arg("wins", profile.wins)
+                    .arg("rate", format!("{:.0}", profile.win_rate())),
+                WHITE,
+            ),
+            (
+                Message::new("stats.streak")
+                    .arg("current", profile.current_streak)
+                    .arg("best", profile.best_streak),
+                WHITE,
+            ),
+            (
+                Message::new("stats.totals")
+                    .arg("moves", profile.moves)
+                    .arg("shots", profile.shots)
+                    .arg("rides", profile.bat_rides),
+                WHITE,
+            ),
+            (
+                Message::new("stats.losses").arg("losses", losses.total()),
+                RED,
+            ),
+        ];
+        for (cause, count) in [
+            ("outcome.eaten_by_wumpus", losses.eaten_by_wumpus),
+            ("outcome.fell_in_pit", losses.fell_in_pit),
+            ("outcome.shot_self", losses.shot_self),
+            ("outcome.out_of_arrows", losses.out_of_arrows),
+        ] {
+            lines.push((
+                Message::new("stats.loss")
+                    .arg("cause", cause)
+                    .arg("count", count),
+                LIGHTGRAY,
+            ));
+        }
+        if profile.best_times.is_empty() {
+            lines.push((Message::new("stats.no_wins"), GREEN));
+        }
+        for (difficulty, time) in &profile.best_times {
+            lines.push((
+                Message::new("stats.best_time")
+                    .arg("difficulty", difficulty.key())
+                    .arg("time", format!("{:.1}", time)),
+                GREEN,
+            ));
+        }
+
+        for (i, (line, color)) in lines.iter().enumerate() {
+            let text = self.text(line);
+            let size = measure_text(&text, None, self.font_size as u16, 1.0);
+            draw_text(
+                &text,
+                SCREEN_WIDTH / 2.0 - size.width / 2.0,
+                150.0 + i as f32 * 30.0,
+                self.font_size,
+                *color,
+            );
+        }
+
+        let back = self.tr("stats.back");
+        let back_size = measure_text(back, None, self.font_size as u16, 1.0);
+        draw_text(
+            back,
+            SCREEN_WIDTH / 2.0 - back_size.width / 2.0,
+            SCREEN_HEIGHT - 40.0,
+            self.font_size,
+            GRAY,
+        );
+    }
+
+    pub fn draw_settings(&self, settings: &Settings, cursor: usize) {
+        clear_background(BLACK);
+
+        let title = self.tr("settings.title");
+        let title_size = measure_text(title, None, self.title_font_size as u16, 1.0);
+        draw_text(
+            title,
+            SCREEN_WIDTH / 2.0 - title_size.width / 2.0,
+            80.0,
+            self.title_font_size,
+            GOLD,
+        );
+
+        let switch = |on: bool| if on { "settings.on" } else { "settings.off" };
+        for (i, setting) in Setting::ALL.iter().enumerate() {
+            let (label, value) = match setting {
+                Setting::Language => (
+                    "settings.language",
+                    settings.language.map_or_else(
+                        || self.tr("settings.system").to_string(),
+                        |language| language.name().to_string(),
+                    ),
+                ),
+                Setting::Difficulty => (
+                    "settings.difficulty",
+                    self.tr(settings.difficulty.key()).to_string(),
+                ),
+                Setting::Practice => (
+                    "settings.practice",
+                    self.tr(switch(settings.practice)).to_string(),
+                ),
+                Setting::Cave => ("settings.cave", self.tr(settings.cave.key()).to_string()),
+                Setting::Layout => (
+                    "settings.layout",
+                    self.tr(settings.layout.key()).to_string(),
+                ),
+                Setting::Volume => (
+                    "settings.volume",
+                    format!("{:.0}%", settings.volume * 100.0),
+                ),
+                Setting::Sound => (
+                    "settings.sound",
+                    self.tr(switch(!settings.muted)).to_string(),
+                ),
+                Setting::Colorblind => (
+                    "settings.colorblind",
+                    self.tr(switch(settings.colorblind)).to_string(),
+                ),
+                Setting::WindowSize => (
+                    "settings.window",
+                    format!("{} x {}", settings.window_width, settings.window_height),
+                ),
+                Setting::Fullscreen => (
+                    "settings.fullscreen",
+                    self.tr(switch(settings.fullscreen)).to_string(),
+                ),
+                Setting::AnimationSpeed => (
+                    "settings.animation",
+                    format!("{}x", settings.animation_speed),
+                ),
+            };
+
+            let y = 150.0 + i as f32 * 36.0;
+            let color = if i == cursor { GOLD } else { LIGHTGRAY };
+            draw_text(self.tr(label), 160.0, y, self.font_size, color);
+            let value = if i == cursor {
+                format!("< {} >", value)
+            } else {
+                value
+            };
+            draw_text(&value, 440.0, y, self.font_size, color);
+        }
+
+        let help = self.tr("settings.help");
+        let help_size = measure_text(help, None, self.font_size as u16, 1.0);
+        draw_text(
+            help,
+            SCREEN_WIDTH / 2.0 - help_size.width / 2.0,
+            SCREEN_HEIGHT - 40.0,
+            self.font_size,
+            GRAY,
+        );
     }
 
+    /// The room under `mouse_pos`, among those drawn: under the fog of war,
+    /// only the rooms the player knows about.
     pub fn get_clicked_room(&self, model: &GameModel, mouse_pos: Vec2) -> Option<usize> {
+        let fog = self.fog_of_war && !model.game_over;
+        let known = model.known_rooms();
         for (i, pos) in model.room_positions.iter().enumerate() {
-            if (mouse_pos - *pos).length() <= ROOM_RADIUS {
+            if (!fog || known[i]) && (mouse_pos - *pos).length() <= ROOM_RADIUS {
                 return Some(i);
             }
         }
@@ -0,0 +1,69 @@
+// Gamepad input, available when the crate is built with the `gamepad` feature.
+// Without it every query reports that no button was pressed, so the rest of
+// the front-end does not need to care whether a pad is connected.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadButton {
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    South, // A / Cross
+    East,  // B / Circle
+    West,  // X / Square
+    North, // Y / Triangle
+    Start,
+}
+
+pub struct GamepadInput {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    pressed: Vec<GamepadButton>,
+}
+
+impl GamepadInput {
+    pub fn new() -> Self {
+        GamepadInput {
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new().ok(),
+            pressed: Vec::new(),
+        }
+    }
+
+    /// Collects the buttons pressed since the previous frame. Must be called
+    /// once per frame before querying `is_pressed`.
+    pub fn poll(&mut self) {
+        self.pressed.clear();
+
+        #[cfg(feature = "gamepad")]
+        if let Some(gilrs) = self.gilrs.as_mut() {
+            while let Some(gilrs::Event { event, .. }) = gilrs.next_event() {
+                if let gilrs::EventType::ButtonPressed(button, _) = event
+                    && let Some(button) = GamepadInput::map_button(button)
+                {
+                    self.pressed.push(button);
+                }
+            }
+        }
+    }
+
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn map_button(button: gilrs::Button) -> Option<GamepadButton> {
+        match button {
+            gilrs::Button::DPadUp => Some(GamepadButton::DPadUp),
+            gilrs::Button::DPadDown => Some(GamepadButton::DPadDown),
+            gilrs::Button::DPadLeft => Some(GamepadButton::DPadLeft),
+            gilrs::Button::DPadRight => Some(GamepadButton::DPadRight),
+            gilrs::Button::South => Some(GamepadButton::South),
+            gilrs::Button::East => Some(GamepadButton::East),
+            gilrs::Button::West => Some(GamepadButton::West),
+            gilrs::Button::North => Some(GamepadButton::North),
+            gilrs::Button::Start => Some(GamepadButton::Start),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, io, path::Path};
+
+/// A player-facing text: the key of a translated template and the values of
+/// its `{name}` placeholders.
+///
+/// The rules engine only produces messages; turning them into words is up to
+/// the front-end, through [`Translations`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    pub key: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl Message {
+    pub fn new(key: impl Into<String>) -> Self {
+        Message {
+            key: key.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Sets the value of the `{name}` placeholder.
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.args.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Languages shipped with the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    Italian,
+    English,
+    German,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::Italian,
+        Language::English,
+        Language::German,
+        Language::Spanish,
+    ];
+
+    /// Language used for any text missing from a translation.
+    pub const FALLBACK: Language = Language::English;
+
+    /// ISO 639-1 code, also the name of the translation file.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Italian => "it",
+            Language::English => "en",
+            Language::German => "de",
+            Language::Spanish => "es",
+        }
+    }
+
+    /// Name of the language in the language itself.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Italian => "Italiano",
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::Spanish => "Español",
+        }
+    }
+
+    /// Parses a language code, also in locale form such as `de_DE.UTF-8`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.get(..2)?.to_ascii_lowercase();
+        Language::ALL.into_iter().find(|l| l.code() == code)
+    }
+
+    /// Language of the user's locale, from the usual environment variables.
+    pub fn from_env() -> Option<Self> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Language::from_code(&value))
+    }
+
+    pub fn next(self) -> Self {
+        let i = Language::ALL.iter().position(|&l| l == self).unwrap();
+        Language::ALL[(i + 1) % Language::ALL.len()]
+    }
+
+    fn builtin(self) -> &'static str {
+        match self {
+            Language::Italian => include_str!("../assets/lang/it.json"),
+            Language::English => include_str!("../assets/lang/en.json"),
+            Language::German => include_str!("../assets/lang/de.json"),
+            Language::Spanish => include_str!("../assets/lang/es.json"),
+        }
+    }
+}
+
+type Catalog = HashMap<String, String>;
+
+/// The texts of one language, backed by the fallback language.
+///
+/// Translations are flat JSON objects mapping message keys to templates.
+/// The built-in ones are compiled in; files found on disk override or extend
+/// them.
+#[derive(Debug, Clone)]
+pub struct Translations {
+    language: Language,
+    texts: Catalog,
+    fallback: Catalog,
+}
+
+impl Translations {
+    /// The built-in texts of `language`.
+    pub fn new(language: Language) -> Self {
+        Translations {
+            language,
+            texts: builtin_catalog(language),
+            fallback: builtin_catalog(Language::FALLBACK),
+        }
+    }
+
+    /// The built-in texts of `language`, overridden by `<code>.json` files
+    /// in `dir` when present.
+    pub fn with_overrides(language: Language, dir: &Path) -> io::Result<Self> {
+        let mut translations = Translations::new(language);
+        merge_file(&mut translations.texts, &dir.join(file_name(language)))?;
+        merge_file(
+            &mut translations.fallback,
+            &dir.join(file_name(Language::FALLBACK)),
+        )?;
+        Ok(translations)
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Template of `key`, from the fallback language if the current one
+    /// lacks it, or the key itself as a last resort.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.texts
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// The text of `message`, with its placeholders filled in. Values that
+    /// are message keys themselves, such as the name of a cave, are
+    /// translated too.
+    pub fn format(&self, message: &Message) -> String {
+        let mut text = self.get(&message.key).to_string();
+        for (name, value) in &message.args {
+            text = text.replace(&format!("{{{}}}", name), self.get(value));
+        }
+        text
+    }
+
+    /// The texts of several messages, as sentences of one paragraph.
+    pub fn format_all(&self, messages: &[Message]) -> String {
+        messages
+            .iter()
+            .map(|message| self.format(message))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn file_name(language: Language) -> String {
+    format!("{}.json", language.code())
+}
+
+fn builtin_catalog(language: Language) -> Catalog {
+    serde_json::from_str(language.builtin()).expect("built-in translation is valid JSON")
+}
+
+// Adds the texts of a translation file; a missing file changes nothing
+fn merge_file(catalog: &mut Catalog, path: &Path) -> io::Result<()> {
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let texts: Catalog = serde_json::from_str(&json)?;
+    catalog.extend(texts);
+    Ok(())
+}
@@ -0,0 +1,495 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, f32::consts::PI};
+
+use crate::topology::{CaveKind, CaveTopology};
+
+/// Size of the virtual screen the cave is laid out on; front-ends scale it
+/// to their window.
+pub const SCREEN_WIDTH: f32 = 800.0;
+pub const SCREEN_HEIGHT: f32 = 600.0;
+
+// Hand-tuned projection of the dodecahedron: three concentric rings of
+// 5, 10 and 5 rooms, numbered from the outside in
+const NUM_RINGS: usize = 3;
+pub const RING_RADII: [f32; NUM_RINGS] = [200.0, 140.0, 60.0];
+pub const NODES_PER_RING: [usize; NUM_RINGS] = [5, 10, 5];
+pub const START_ANGLES: [f32; NUM_RINGS] = [-0.5 * PI, -0.5 * PI, 0.5 * PI];
+
+// Force-directed simulation parameters
+const FORCE_ITERATIONS: usize = 300;
+const FORCE_START_TEMPERATURE: f32 = 0.1;
+
+// Barycentric relaxation steps of the planar embedding
+const PLANAR_ITERATIONS: usize = 500;
+
+// Minimum distances, relative to the radius of the map, kept between two
+// rooms and between a room and a tunnel it does not belong to
+const ROOM_SPACING: f32 = 0.15;
+const EDGE_CLEARANCE: f32 = 0.1;
+const CLEARANCE_ITERATIONS: usize = 100;
+
+/// How room positions are computed from the cave graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LayoutStrategy {
+    /// Rooms on concentric rings by distance from the centre of the graph.
+    ConcentricRings,
+    /// Spring simulation where tunnels attract and rooms repel each other.
+    ForceDirected,
+    /// Tutte embedding: a short cycle, a face of the cave when one can be
+    /// found, is pinned on a convex polygon and every other room sits at the
+    /// barycentre of its neighbours. Caves that are planar and 3-connected,
+    /// like the dodecahedron, are drawn without crossing tunnels; others may
+    /// still show some.
+    PlanarEmbedding,
+}
+
+impl LayoutStrategy {
+    pub const ALL: [LayoutStrategy; 3] = [
+        LayoutStrategy::ConcentricRings,
+        LayoutStrategy::ForceDirected,
+        LayoutStrategy::PlanarEmbedding,
+    ];
+
+    /// The strategy that draws a given kind of cave best.
+    pub fn default_for(kind: CaveKind) -> Self {
+        match kind {
+            CaveKind::Dodecahedron | CaveKind::Dendrite => LayoutStrategy::ConcentricRings,
+            _ => LayoutStrategy::ForceDirected,
+        }
+    }
+
+    /// Message key of the strategy's name.
+    pub fn key(self) -> &'static str {
+        match self {
+            LayoutStrategy::ConcentricRings => "layout.concentric_rings",
+            LayoutStrategy::ForceDirected => "layout.force_directed",
+            LayoutStrategy::PlanarEmbedding => "layout.planar_embedding",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = LayoutStrategy::ALL.iter().position(|&s| s == self).unwrap();
+        LayoutStrategy::ALL[(i + 1) % LayoutStrategy::ALL.len()]
+    }
+}
+
+/// Circular region of the screen the map is fitted into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutArea {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+/// Computes the position of every room of `cave`, fitted into `area`.
+///
+/// Layouts are deterministic: the same cave always gets the same drawing.
+pub fn compute_layout(
+    cave: &CaveTopology,
+    strategy: LayoutStrategy,
+    area: LayoutArea,
+) -> Vec<Vec2> {
+    let neighbors = undirected_neighbors(cave);
+
+    let positions = match strategy {
+        LayoutStrategy::ConcentricRings if cave.kind() == CaveKind::Dodecahedron => {
+            // Already crossing-free by construction
+            return dodecahedron_rings(area);
+        }
+        LayoutStrategy::ConcentricRings => concentric_rings(&neighbors),
+        LayoutStrategy::ForceDirected => force_directed(&neighbors),
+        LayoutStrategy::PlanarEmbedding => {
+            planar_embedding(&neighbors).unwrap_or_else(|| concentric_rings(&neighbors))
+        }
+    };
+
+    let mut positions = normalize(positions);
+    keep_clearance(&mut positions, &cave.edges());
+    positions
+        .into_iter()
+        .map(|p| area.center + p * area.radius)
+        .collect()
+}
+
+fn node_positions_by_ring(
+    center: Vec2,
+    radius: f32,
+    num_nodes: usize,
+    start_angle_rad: f32,
+) -> Vec<Vec2> {
+    let angle_step = 2.0 * PI / num_nodes.max(1) as f32;
+
+    (0..num_nodes)
+        .map(|i| {
+            let current_angle = start_angle_rad + (i as f32) * angle_step;
+            center + radius * Vec2::new(current_angle.cos(), current_angle.sin())
+        })
+        .collect()
+}
+
+fn dodecahedron_rings(area: LayoutArea) -> Vec<Vec2> {
+    let scale = area.radius / RING_RADII[0];
+
+    (0..NUM_RINGS)
+        .flat_map(|i| {
+            node_positions_by_ring(
+                area.center,
+                RING_RADII[i] * scale,
+                NODES_PER_RING[i],
+                START_ANGLES[i],
+            )
+        })
+        .collect()
+}
+
+// Tunnels ignoring their direction, without duplicates
+fn undirected_neighbors(cave: &CaveTopology) -> Vec<Vec<usize>> {
+    let mut neighbors = vec![Vec::new(); cave.num_rooms()];
+    for (a, b) in cave.edges() {
+        if !neighbors[a].contains(&b) {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+    }
+    neighbors
+}
+
+fn bfs_distances(neighbors: &[Vec<usize>], start: usize) -> Vec<usize> {
+    let mut dist = vec![usize::MAX; neighbors.len()];
+    let mut queue = VecDeque::from([start]);
+    dist[start] = 0;
+    while let Some(room) = queue.pop_front() {
+        for &next in &neighbors[room] {
+            if dist[next] == usize::MAX {
+                dist[next] = dist[room] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+fn concentric_rings(neighbors: &[Vec<usize>]) -> Vec<Vec2> {
+    let n = neighbors.len();
+
+    // The graph centre (smallest eccentricity) goes in the middle
+    let (center, mut dist) = (0..n)
+        .map(|room| (room, bfs_distances(neighbors, room)))
+        .min_by_key(|(_, dist)| *dist.iter().max().unwrap())
+        .unwrap();
+
+    // Rooms the centre cannot reach, in a cave that is not connected, go on
+    // a ring of their own outside the others
+    let reached = dist.iter().copied().filter(|&d| d != usize::MAX);
+    let outside = reached.max().unwrap_or(0) + 1;
+    for d in &mut dist {
+        if *d == usize::MAX {
+            *d = outside;
+        }
+    }
+    let num_layers = (*dist.iter().max().unwrap()).max(1);
+
+    // Breadth-first tree: every room hangs below its first inner neighbour
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&room| (dist[room], room));
+    let mut children = vec![Vec::new(); n];
+    for &room in order.iter().skip(1) {
+        let parent = neighbors[room]
+            .iter()
+            .copied()
+            .filter(|&r| dist[r] + 1 == dist[room])
+            .min()
+            .unwrap_or(center);
+        children[parent].push(room);
+    }
+
+    // Each subtree gets an angular wedge proportional to its leaves, so the
+    // tree tunnels never cross
+    let mut leaves = vec![1usize; n];
+    for &room in order.iter().rev() {
+        if !children[room].is_empty() {
+            leaves[room] = children[room].iter().map(|&c| leaves[c]).sum();
+        }
+    }
+
+    let mut wedges = vec![(0.0f32, 0.0f32); n];
+    let mut positions = vec![Vec2::ZERO; n];
+    wedges[center] = (-0.5 * PI, 1.5 * PI);
+    for &room in &order {
+        let (start, end) = wedges[room];
+        if room != center {
+            let angle = (start + end) / 2.0;
+            let radius = dist[room] as f32 / num_layers as f32;
+            positions[room] = radius * Vec2::new(angle.cos(), angle.sin());
+        }
+
+        let mut from = start;
+        for &child in &children[room] {
+            let to = from + (end - start) * leaves[child] as f32 / leaves[room] as f32;
+            wedges[child] = (from, to);
+            from = to;
+        }
+    }
+
+    positions
+}
+
+// Fruchterman-Reingold spring embedder, started from a circle so that the
+// result does not depend on any random generator
+fn force_directed(neighbors: &[Vec<usize>]) -> Vec<Vec2> {
+    let n = neighbors.len();
+    let k = (1.0 / n as f32).sqrt();
+    let mut positions = node_positions_by_ring(Vec2::ZERO, 0.5, n, -0.5 * PI);
+
+    for iteration in 0..FORCE_ITERATIONS {
+        let temperature =
+            FORCE_START_TEMPERATURE * (1.0 - iteration as f32 / FORCE_ITERATIONS as f32);
+        let mut displacement = vec![Vec2::ZERO; n];
+
+        for a in 0..n {
+            for b in a + 1..n {
+                let delta = positions[a] - positions[b];
+                let distance = delta.length().max(0.001);
+                let force = delta / distance * (k * k / distance);
+                displacement[a] += force;
+                displacement[b] -= force;
+            }
+        }
+
+        for (a, exits) in neighbors.iter().enumerate() {
+            for &b in exits.iter().filter(|&&b| a < b) {
+                let delta = positions[a] - positions[b];
+                let distance = delta.length().max(0.001);
+                let force = delta / distance * (distance * distance / k);
+                displacement[a] -= force;
+                displacement[b] += force;
+            }
+        }
+
+        for (position, shift) in positions.iter_mut().zip(displacement) {
+            let length = shift.length();
+            if length > 0.0 {
+                *position += shift / length * length.min(temperature);
+            }
+        }
+    }
+
+    positions
+}
+
+fn planar_embedding(neighbors: &[Vec<usize>]) -> Option<Vec<Vec2>> {
+    let outer = outer_cycle(neighbors)?;
+    let n = neighbors.len();
+
+    let mut fixed = vec![false; n];
+    let mut positions = vec![Vec2::ZERO; n];
+    for (room, position) in outer.iter().zip(node_positions_by_ring(
+        Vec2::ZERO,
+        1.0,
+        outer.len(),
+        -0.5 * PI,
+    )) {
+        fixed[*room] = true;
+        positions[*room] = position;
+    }
+
+    for _ in 0..PLANAR_ITERATIONS {
+        for room in (0..n).filter(|&r| !fixed[r]) {
+            let sum: Vec2 = neighbors[room].iter().map(|&r| positions[r]).sum();
+            positions[room] = sum / neighbors[room].len() as f32;
+        }
+    }
+
+    Some(positions)
+}
+
+// Cycle to pin on the outside: the shortest peripheral one, i.e. without
+// chords and leaving the other rooms connected, which in a 3-connected
+// planar graph is exactly a face. Falls back on the shortest cycle; None for
+// a tree
+fn outer_cycle(neighbors: &[Vec<usize>]) -> Option<Vec<usize>> {
+    let mut cycles = short_cycles(neighbors);
+    cycles.sort_by_key(Vec::len);
+    cycles
+        .iter()
+        .find(|cycle| is_peripheral(neighbors, cycle))
+        .or(cycles.first())
+        .cloned()
+}
+
+fn is_peripheral(neighbors: &[Vec<usize>], cycle: &[usize]) -> bool {
+    let mut on_cycle = vec![false; neighbors.len()];
+    for &room in cycle {
+        on_cycle[room] = true;
+    }
+
+    // Each room of the cycle only touches its two neighbours on it
+    let chordless = cycle.iter().all(|&room| {
+        neighbors[room]
+            .iter()
+            .filter(|&&next| on_cycle[next])
+            .count()
+            == 2
+    });
+    if !chordless {
+        return false;
+    }
+
+    let Some(start) = (0..neighbors.len()).find(|&room| !on_cycle[room]) else {
+        return true;
+    };
+    let mut reached = on_cycle;
+    reached[start] = true;
+    let mut queue = VecDeque::from([start]);
+    while let Some(room) = queue.pop_front() {
+        for &next in &neighbors[room] {
+            if !reached[next] {
+                reached[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    reached.iter().all(|&r| r)
+}
+
+// The shortest cycle through each tunnel, in order
+fn short_cycles(neighbors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut cycles: Vec<Vec<usize>> = Vec::new();
+
+    for (a, exits) in neighbors.iter().enumerate() {
+        for &b in exits.iter().filter(|&&b| a < b) {
+            // Shortest path from b back to a without using the tunnel a-b
+            let mut parent = vec![usize::MAX; neighbors.len()];
+            let mut queue = VecDeque::from([b]);
+            parent[b] = b;
+            while let Some(room) = queue.pop_front() {
+                if room == a {
+                    break;
+                }
+                for &next in &neighbors[room] {
+                    if parent[next] == usize::MAX && !(room == b && next == a) {
+                        parent[next] = room;
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            if parent[a] == usize::MAX {
+                continue;
+            }
+            let mut cycle = vec![a];
+            while let Some(&last) = cycle.last()
+                && last != b
+            {
+                cycle.push(parent[last]);
+            }
+            cycles.push(cycle);
+        }
+    }
+
+    cycles
+}
+
+// Centres the drawing on the origin and scales it into the unit circle
+fn normalize(mut positions: Vec<Vec2>) -> Vec<Vec2> {
+    let min = positions
+        .iter()
+        .copied()
+        .reduce(Vec2::min)
+        .unwrap_or_default();
+    let max = positions
+        .iter()
+        .copied()
+        .reduce(Vec2::max)
+        .unwrap_or_default();
+    let center = (min + max) / 2.0;
+    let radius = positions
+        .iter()
+        .map(|p| (*p - center).length())
+        .fold(0.0, f32::max)
+        .max(f32::EPSILON);
+
+    for p in positions.iter_mut() {
+        *p = (*p - center) / radius;
+    }
+    positions
+}
+
+// Pushes rooms away from each other and from tunnels passing over them
+fn keep_clearance(positions: &mut [Vec2], edges: &[(usize, usize)]) {
+    for _ in 0..CLEARANCE_ITERATIONS {
+        let mut moved = false;
+
+        for room in 0..positions.len() {
+            for other in 0..positions.len() {
+                let delta = positions[room] - positions[other];
+                let distance = delta.length();
+                if room != other && distance < ROOM_SPACING {
+                    let direction = if distance > 0.0 {
+                        delta / distance
+                    } else {
+                        Vec2::X
+                    };
+                    positions[room] += direction * (ROOM_SPACING - distance) / 2.0;
+                    moved = true;
+                }
+            }
+
+            for &(a, b) in edges.iter().filter(|&&(a, b)| a != room && b != room) {
+                let (start, end) = (positions[a], positions[b]);
+                let segment = end - start;
+                let t = ((positions[room] - start).dot(segment)
+                    / segment.length_squared().max(f32::EPSILON))
+                .clamp(0.0, 1.0);
+                let closest = start + segment * t;
+                let delta = positions[room] - closest;
+                let distance = delta.length();
+                if distance < EDGE_CLEARANCE {
+                    let direction = if distance > 0.0 {
+                        delta / distance
+                    } else {
+                        segment.perp().normalize_or_zero()
+                    };
+                    positions[room] += direction * (EDGE_CLEARANCE - distance);
+                    moved = true;
+                }
+            }
+        }
+
+        if !moved {
+            break;
+        }
+    }
+
+    // The pushes may have spilled out of the unit circle
+    let radius = positions.iter().map(|p| p.length()).fold(1.0, f32::max);
+    for p in positions.iter_mut() {
+        *p /= radius;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caves_in_pieces_are_laid_out() {
+        // Two separate rings of four rooms, which no playable cave has but a
+        // broken save could
+        let cave: CaveTopology = serde_json::from_value(serde_json::json!({
+            "kind": "Random",
+            "tunnels": [[1, 3], [0, 2], [1, 3], [2, 0], [5, 7], [4, 6], [5, 7], [6, 4]],
+            "directed": false,
+        }))
+        .unwrap();
+        let area = LayoutArea {
+            center: Vec2::ZERO,
+            radius: 1.0,
+        };
+        for strategy in LayoutStrategy::ALL {
+            let positions = compute_layout(&cave, strategy, area);
+            assert_eq!(positions.len(), cave.num_rooms());
+            assert!(positions.iter().all(|p| p.is_finite()));
+        }
+    }
+}
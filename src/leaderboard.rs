@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+use crate::difficulty::Difficulty;
+use crate::game_model::GameModel;
+use crate::topology::CaveKind;
+
+/// Entries kept in each table of the leaderboard.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// Points of every win, before the bonuses and penalties
+const BASE_SCORE: f64 = 1000.0;
+// Lowest score of a win, however long it took
+const MIN_SCORE: f64 = 100.0;
+
+/// Points earned by a won game, `None` for any other.
+///
+/// Every win starts from the same base; arrows left add to it, moves and
+/// seconds played take from it, and the result is multiplied by how hard
+/// the difficulty and the kind of cave are.
+pub fn score(model: &GameModel) -> Option<u32> {
+    if !model.win || model.is_practice() {
+        return None;
+    }
+    let stats = model.stats();
+    let points =
+        BASE_SCORE + 100.0 * model.arrows as f64 - 10.0 * stats.moves as f64 - stats.time.max(0.0);
+    let factor = difficulty_factor(model.difficulty) * cave_factor(model.cave.kind());
+    Some((points.max(MIN_SCORE) * factor).round() as u32)
+}
+
+fn difficulty_factor(difficulty: Difficulty) -> f64 {
+    match difficulty {
+        Difficulty::Easy => 0.5,
+        Difficulty::Classic => 1.0,
+        Difficulty::Hard => 1.5,
+        Difficulty::Nightmare => 2.0,
+    }
+}
+
+// Caves with dead ends or one-way tunnels are harder to hunt in
+fn cave_factor(kind: CaveKind) -> f64 {
+    match kind {
+        CaveKind::StringOfBeads => 0.9,
+        CaveKind::Dodecahedron | CaveKind::MobiusStrip => 1.0,
+        CaveKind::HexNetwork => 1.1,
+        CaveKind::Dendrite | CaveKind::Random => 1.2,
+        CaveKind::OneWayLattice => 1.3,
+    }
+}
+
+/// A won game worth remembering.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    pub difficulty: Difficulty,
+    pub cave: CaveKind,
+    pub moves: u32,
+    /// Seconds played.
+    pub time: f64,
+    pub seed: u64,
+}
+
+impl HighScore {
+    /// The entry of a won game, `None` for any other.
+    pub fn new(model: &GameModel, name: &str) -> Option<Self> {
+        let stats = model.stats();
+        Some(HighScore {
+            name: name.trim().to_string(),
+            score: score(model)?,
+            difficulty: model.difficulty,
+            cave: model.cave.kind(),
+            moves: stats.moves,
+            time: stats.time,
+            seed: model.seed(),
+        })
+    }
+}
+
+/// Best scores, in a table of [`LEADERBOARD_SIZE`] entries for each
+/// difficulty and kind of cave.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    entries: Vec<HighScore>,
+    /// Name given with the last entry, offered again for the next one.
+    pub last_name: String,
+}
+
+impl Leaderboard {
+    /// The table of a difficulty and kind of cave, best score first.
+    pub fn table(&self, difficulty: Difficulty, cave: CaveKind) -> Vec<&HighScore> {
+        self.ranked(difficulty, cave)
+            .into_iter()
+            .map(|i| &self.entries[i])
+            .collect()
+    }
+
+    /// Whether a score would enter its table.
+    pub fn qualifies(&self, difficulty: Difficulty, cave: CaveKind, score: u32) -> bool {
+        self.rank(difficulty, cave, score) < LEADERBOARD_SIZE
+    }
+
+    /// Adds an entry to its table, dropping the lowest one if the table
+    /// is full. Returns its rank from 0, or `None` if it did not make it.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self.rank(entry.difficulty, entry.cave, entry.score);
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        let mut dropped = self.ranked(entry.difficulty, entry.cave);
+        dropped.drain(..dropped.len().min(LEADERBOARD_SIZE - 1));
+        dropped.sort_unstable();
+        for i in dropped.into_iter().rev() {
+            self.entries.remove(i);
+        }
+
+        self.last_name = entry.name.clone();
+        self.entries.push(entry);
+        Some(rank)
+    }
+
+    /// Reads the leaderboard from `path`; a missing file is an empty one.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the leaderboard to `path`, creating the parent directories if
+    /// needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    // Indices of the entries of a table, best score first; among equal
+    // scores the older entry ranks higher
+    fn ranked(&self, difficulty: Difficulty, cave: CaveKind) -> Vec<usize> {
+        let mut ranked: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| self.entries[i].difficulty == difficulty && self.entries[i].cave == cave)
+            .collect();
+        ranked.sort_by(|&a, &b| self.entries[b].score.cmp(&self.entries[a].score));
+        ranked
+    }
+
+    // Place a new score would take in its table
+    fn rank(&self, difficulty: Difficulty, cave: CaveKind, score: u32) -> usize {
+        self.ranked(difficulty, cave)
+            .into_iter()
+            .take_while(|&i| self.entries[i].score >= score)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::Arc;
+
+    // A game won with two arrows left after `moves` moves and `seconds`
+    fn won(difficulty: Difficulty, cave: CaveKind, moves: u32, seconds: f64) -> GameModel {
+        let mut model = GameModel::with_seed_and_clock(1, Arc::new(ManualClock::new(0.0)));
+        model.set_cave_kind(cave);
+        model.set_difficulty(difficulty);
+        model.arrows = 2;
+        model.moves_count = moves;
+        model.win = true;
+        model.game_over = true;
+        model.start_time = 0.0;
+        model.end_time = seconds;
+        model
+    }
+
+    fn entry(name: &str, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            difficulty: Difficulty::Classic,
+            cave: CaveKind::Dodecahedron,
+            moves: 10,
+            time: 60.0,
+            seed: 1,
+        }
+    }
+
+    fn names(board: &Leaderboard) -> Vec<&str> {
+        board
+            .table(Difficulty::Classic, CaveKind::Dodecahedron)
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn scores_add_arrows_and_take_moves_and_time() {
+        let model = won(Difficulty::Classic, CaveKind::Dodecahedron, 10, 20.0);
+        assert_eq!(score(&model), Some(1000 + 200 - 100 - 20));
+    }
+
+    #[test]
+    fn every_win_scores_at_least_the_minimum() {
+        let model = won(Difficulty::Classic, CaveKind::Dodecahedron, 500, 3600.0);
+        assert_eq!(score(&model), Some(100));
+        let model = won(Difficulty::Easy, CaveKind::Dodecahedron, 500, 3600.0);
+        assert_eq!(score(&model), Some(50));
+    }
+
+    #[test]
+    fn harder_games_score_more() {
+        let points = |difficulty, cave| score(&won(difficulty, cave, 10, 20.0)).unwrap();
+        let classic = points(Difficulty::Classic, CaveKind::Dodecahedron);
+        assert_eq!(
+            points(Difficulty::Easy, CaveKind::Dodecahedron),
+            classic / 2
+        );
+        assert_eq!(
+            points(Difficulty::Nightmare, CaveKind::Dodecahedron),
+            classic * 2
+        );
+        assert_eq!(
+            points(Difficulty::Classic, CaveKind::OneWayLattice),
+            (classic as f64 * 1.3).round() as u32
+        );
+        assert_eq!(
+            points(Difficulty::Hard, CaveKind::StringOfBeads),
+            (classic as f64 * 1.5 * 0.9).round() as u32
+        );
+    }
+
+    #[test]
+    fn only_scored_wins_count() {
+        let mut model = won(Difficulty::Classic, CaveKind::Dodecahedron, 10, 20.0);
+        model.win = false;
+        assert_eq!(score(&model), None);
+
+        let mut model = won(Difficulty::Classic, CaveKind::Dodecahedron, 10, 20.0);
+        model.set_practice(true);
+        model.win = true;
+        assert_eq!(score(&model), None);
+    }
+
+    #[test]
+    fn tables_rank_best_first() {
+        let mut board = Leaderboard::default();
+        assert_eq!(board.insert(entry("b", 300)), Some(0));
+        assert_eq!(board.insert(entry("a", 500)), Some(0));
+        assert_eq!(board.insert(entry("c", 100)), Some(2));
+        assert_eq!(names(&board), ["a", "b", "c"]);
+        assert_eq!(board.last_name, "c");
+    }
+
+    #[test]
+    fn older_entries_win_ties() {
+        let mut board = Leaderboard::default();
+        board.insert(entry("first", 500));
+        assert_eq!(board.insert(entry("second", 500)), Some(1));
+        assert_eq!(names(&board), ["first", "second"]);
+    }
+
+    #[test]
+    fn full_tables_drop_the_lowest_entry() {
+        let mut board = Leaderboard::default();
+        let mut other = entry("other", 1);
+        other.difficulty = Difficulty::Hard;
+        board.insert(other);
+        for score in 1..=15 {
+            board.insert(entry(&score.to_string(), score * 100));
+        }
+
+        let table = board.table(Difficulty::Classic, CaveKind::Dodecahedron);
+        assert_eq!(table.len(), LEADERBOARD_SIZE);
+        assert_eq!(table.last().unwrap().score, 600);
+
+        // Tying the lowest entry is not enough to enter a full table
+        assert!(!board.qualifies(Difficulty::Classic, CaveKind::Dodecahedron, 600));
+        assert_eq!(board.insert(entry("late", 600)), None);
+        assert!(board.qualifies(Difficulty::Classic, CaveKind::Dodecahedron, 601));
+
+        // Other tables keep their own entries
+        assert_eq!(
+            board.table(Difficulty::Hard, CaveKind::Dodecahedron).len(),
+            1
+        );
+    }
+}
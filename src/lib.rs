@@ -0,0 +1,48 @@
+//! Headless rules engine for Hunt the Wumpus.
+//!
+//! Nothing in this crate depends on a window or on the real clock: the
+//! macroquad front-end in `main.rs` is just one consumer of the model, and
+//! bots, servers and tests can drive the same rules directly.
+//!
+//! The macroquad front-end is behind the default `gui` feature: build with
+//! `--no-default-features` to get the library and the text version alone.
+
+pub mod action;
+pub mod agent;
+pub mod cave_map;
+pub mod clock;
+pub mod controls;
+pub mod daily;
+pub mod difficulty;
+pub mod events;
+pub mod game_model;
+pub mod i18n;
+pub mod layout;
+pub mod leaderboard;
+pub mod profile;
+pub mod replay;
+pub mod save;
+pub mod settings;
+pub mod solver;
+pub mod stats;
+pub mod topology;
+
+pub use action::Action;
+pub use agent::{Agent, Percept};
+pub use cave_map::{CaveMap, ClueSources, Clues, Marker};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use controls::{PlayCommand, PlayControls};
+pub use daily::{DailyHistory, Date, ShareError};
+pub use difficulty::{Difficulty, Rules};
+pub use events::{GameEvent, LogEntry};
+pub use game_model::{GameError, GameModel, GameRng, MAX_ARROW_PATH};
+pub use i18n::{Language, Message, Translations};
+pub use layout::{LayoutArea, LayoutStrategy};
+pub use leaderboard::{HighScore, LEADERBOARD_SIZE, Leaderboard};
+pub use profile::{Losses, Profile};
+pub use replay::Replay;
+pub use save::{SAVE_VERSION, SaveError, SaveGame};
+pub use settings::Settings;
+pub use solver::{Decision, Solver};
+pub use stats::{GameStats, Outcome};
+pub use topology::{CaveKind, CaveTopology};
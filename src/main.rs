@@ -1,22 +1,29 @@
 // Hunt the Wumpus implementato in Rust con pattern MVC e macroquad
 // Struttura del progetto:
-// - Model: gestisce la logica di gioco
+// - Model: gestisce la logica di gioco (crate libreria, senza macroquad)
 // - View: gestisce la rappresentazione grafica
 // - Controller: gestisce gli input dell'utente e aggiorna il model
 
-use std::f32::consts::PI;
+use std::{path::PathBuf, sync::Arc};
 
 use macroquad::prelude::*;
 
+mod audio;
 mod game_controller; // Add this line to include the new module
-mod game_model;
 mod game_state;
 mod game_view; // Add this line to include the new module
+mod gamepad;
 
+use audio::{Audio, VOLUME_STEP};
 use game_controller::GameController; // Import GameController from the new module
-use game_model::GameModel;
-use game_state::GameState;
-use game_view::GameView; // Import GameView from the new module
+use game_state::{GameState, Setting};
+use game_view::{GameView, Palette}; // Import GameView from the new module
+use gamepad::GamepadButton;
+use rustedbytes_wumpus::{
+    CaveKind, DailyHistory, Date, Difficulty, GameModel, HighScore, Language, Leaderboard, Message,
+    PlayCommand, Profile, Replay, Settings, SystemClock, Translations, daily, events, leaderboard,
+    save,
+};
 
 // ----- COSTANTI -----
 //const NUM_ROOMS: usize = 20;
@@ -24,57 +31,175 @@ use game_view::GameView; // Import GameView from the new module
 //const NUM_BATS: usize = 2;
 //const NUM_PITS: usize = 2;
 
-const SCREEN_WIDTH: f32 = 800.0;
-const SCREEN_HEIGHT: f32 = 600.0;
-//const ROOM_RADIUS: f32 = 12.0;
+// Nome del file di salvataggio nella cartella dati dell'utente
+const SAVE_FILE_NAME: &str = "savegame.json";
 
-// Numero di cerchi concentrici in cui sono disposti i nodi
-const NUM_RINGS: usize = 3;
+// Nome del file con le statistiche del giocatore
+const PROFILE_FILE_NAME: &str = "profile.json";
 
-// Raggi delle 3 circonferenze concentriche
-pub const RING_RADII: [f32; NUM_RINGS] = [200.0, 140.0, 60.0];
+// Nome del file con i punteggi migliori
+const LEADERBOARD_FILE_NAME: &str = "leaderboard.json";
 
-// Spessore delle linee guida
-pub const LINE_THICKNESS: f32 = 2.0;
+// Nome del file con le caverne del giorno già giocate
+const DAILY_FILE_NAME: &str = "daily.json";
 
-// Distribuzione dei nodi su ciascuna circonferenza (totale 5 + 10 + 5 = 20)
-pub const NODES_PER_RING: [usize; NUM_RINGS] = [5, 10, 5];
+// Nome del file delle impostazioni nella cartella di configurazione
+const SETTINGS_FILE_NAME: &str = "settings.toml";
 
-// Angolo di partenza per i nodi su ciascuna circonferenza (in radianti)
-// Mettiamo 0.0 per tutti, ma potresti variarli per "ruotare" i set di nodi
-pub const START_ANGLES: [f32; NUM_RINGS] = [-0.5 * PI, -0.5 * PI, 0.5 * PI];
+// Lunghezza massima del nome nella tabella dei punteggi
+const MAX_NAME_LENGTH: usize = 16;
 
 // ----- GAME STATE -----
 struct Game {
     state: GameState,
     model: GameModel,
     view: GameView,
+    audio: Audio,
     controller: GameController,
     splash_timer: f32,
     transition_timer: f32,
+    // Replay of the game just ended, while it is being watched
+    replay: Option<Replay>,
+    // Statistiche di tutte le partite concluse
+    profile: Profile,
+    // Punteggi migliori e tabella mostrata (difficoltà e tipo di caverna)
+    leaderboard: Leaderboard,
+    board: (Difficulty, CaveKind),
+    // Posizione del nuovo record nella tabella mostrata
+    board_highlight: Option<usize>,
+    // Schermata a cui tornare lasciando le tabelle
+    board_return: GameState,
+    // Nome in corso di inserimento per un nuovo record
+    player_name: String,
+    // Data della caverna del giorno in corso, se è quella che si gioca
+    daily: Option<Date>,
+    daily_history: DailyHistory,
+    // Preferenze del giocatore e voce selezionata nella loro schermata
+    settings: Settings,
+    settings_cursor: usize,
 }
 
 impl Game {
-    fn new() -> Self {
-        Game {
+    fn new(seed: Option<u64>, language: Option<Language>, settings: Settings) -> Self {
+        let mut model = match seed {
+            Some(seed) => GameModel::from_seed(seed),
+            None => GameModel::new(),
+        };
+        model.set_cave_kind(settings.cave);
+        model.set_layout(settings.layout);
+        model.set_difficulty(settings.difficulty);
+
+        // La lingua scelta da riga di comando vale solo per questa sessione
+        let language = language
+            .or(settings.language)
+            .or_else(Language::from_env)
+            .unwrap_or(Language::FALLBACK);
+        let mut view = GameView::new();
+        view.translations = load_translations(language);
+
+        let mut game = Game {
             state: GameState::Splash,
-            model: GameModel::new(),
-            view: GameView::new(),
+            model,
+            view,
+            audio: Audio::new(),
             controller: GameController::new(),
             splash_timer: 0.0,
             transition_timer: 0.0,
-        }
+            replay: None,
+            profile: Profile::load(&profile_path()).unwrap_or_else(|err| {
+                eprintln!("Statistiche del giocatore illeggibili: {}", err);
+                Profile::default()
+            }),
+            leaderboard: Leaderboard::load(&leaderboard_path()).unwrap_or_else(|err| {
+                eprintln!("Punteggi migliori illeggibili: {}", err);
+                Leaderboard::default()
+            }),
+            board: (Difficulty::default(), CaveKind::Dodecahedron),
+            board_highlight: None,
+            board_return: GameState::Splash,
+            player_name: String::new(),
+            daily: None,
+            daily_history: DailyHistory::load(&daily_path()).unwrap_or_else(|err| {
+                eprintln!("Caverne del giorno illeggibili: {}", err);
+                DailyHistory::default()
+            }),
+            settings,
+            settings_cursor: 0,
+        };
+        game.apply_settings();
+        game
     }
 
     async fn initialize(&mut self) {
         self.view.load_resources().await;
+        self.audio.load().await;
     }
 
     fn update(&mut self) {
+        self.controller.gamepad.poll();
+
+        // Cambia lingua con F2 in qualsiasi schermata
+        if is_key_pressed(KeyCode::F2) {
+            let language = self.view.translations.language().next();
+            self.view.translations = load_translations(language);
+            self.model
+                .set_message(Message::new("language.changed").arg("language", language.name()));
+            self.settings.language = Some(language);
+            self.save_settings();
+        }
+
+        // Passa allo schermo intero e ritorno con F11
+        if is_key_pressed(KeyCode::F11) {
+            self.settings.fullscreen = !self.settings.fullscreen;
+            set_fullscreen(self.settings.fullscreen);
+            self.save_settings();
+        }
+
+        // F3 spegne o riaccende l'audio, F4 ne cambia il volume
+        if is_key_pressed(KeyCode::F3) {
+            self.audio.toggle_mute();
+            self.settings.muted = self.audio.muted;
+            self.save_settings();
+            let key = if self.audio.muted {
+                "audio.muted"
+            } else {
+                "audio.unmuted"
+            };
+            self.model.set_message(Message::new(key));
+        }
+        if is_key_pressed(KeyCode::F4) {
+            self.audio.next_volume();
+            self.settings.volume = self.audio.volume;
+            self.save_settings();
+            self.model.set_message(
+                Message::new("audio.volume").arg("volume", self.audio.volume_percent()),
+            );
+        }
+
+        let previous = self.state;
         match self.state {
             GameState::Splash => self.update_splash(),
             GameState::Play => self.update_play(),
             GameState::Over => self.update_over(),
+            GameState::Replay => self.update_replay(),
+            GameState::Stats => self.update_stats(),
+            GameState::EnterName => self.update_enter_name(),
+            GameState::Leaderboard => self.update_leaderboard(),
+            GameState::Settings => self.update_settings(),
+        }
+
+        // Il tempo passato nei menu prima di giocare non conta
+        if self.state == GameState::Play && previous != GameState::Play {
+            self.model.start_clock();
+        }
+
+        // Si sentono solo gli eventi della partita in corso, non quelli
+        // rivisti nel replay o di una partita appena caricata
+        match self.state {
+            GameState::Play | GameState::Over | GameState::EnterName => {
+                self.audio.update(&self.model)
+            }
+            _ => self.audio.skip(&self.model),
         }
     }
 
@@ -82,9 +207,80 @@ impl Game {
         // Aggiorna il timer della schermata di splash
         self.splash_timer += get_frame_time();
 
+        // Riprendi la partita salvata premendo C
+        if is_key_pressed(KeyCode::C) && self.load_game() {
+            return;
+        }
+
+        // Scegli il tipo di caverna con le frecce (o il pad direzionale)
+        let pad = &self.controller.gamepad;
+        let cave = self.model.cave.kind();
+        if is_key_pressed(KeyCode::Right) || pad.is_pressed(GamepadButton::DPadRight) {
+            self.settings.cave = cave.next();
+        } else if is_key_pressed(KeyCode::Left) || pad.is_pressed(GamepadButton::DPadLeft) {
+            self.settings.cave = cave.previous();
+        }
+
+        // Scegli la disposizione della mappa con su/giù
+        if is_key_pressed(KeyCode::Up)
+            || is_key_pressed(KeyCode::Down)
+            || pad.is_pressed(GamepadButton::DPadUp)
+            || pad.is_pressed(GamepadButton::DPadDown)
+        {
+            self.settings.layout = self.model.layout.next();
+        }
+
+        // Mostra le statistiche del giocatore con S (o il tasto ovest)
+        if is_key_pressed(KeyCode::S) || pad.is_pressed(GamepadButton::West) {
+            self.state = GameState::Stats;
+            return;
+        }
+
+        // Cambia la difficoltà con D (o il tasto nord del gamepad)
+        if is_key_pressed(KeyCode::D) || pad.is_pressed(GamepadButton::North) {
+            self.settings.difficulty = self.model.difficulty.next();
+        }
+
+        // Gioca in allenamento con P: si può annullare, ma non si segna nulla
+        if is_key_pressed(KeyCode::P) {
+            self.settings.practice = !self.model.is_practice();
+        }
+
+        // Le scelte della prossima partita restano per le sessioni successive
+        if self.settings.cave != cave
+            || self.settings.layout != self.model.layout
+            || self.settings.difficulty != self.model.difficulty
+            || self.settings.practice != self.model.is_practice()
+        {
+            self.apply_settings();
+            self.save_settings();
+            self.splash_timer = 0.0;
+        }
+
+        // Apri le impostazioni con O
+        if is_key_pressed(KeyCode::O) {
+            self.settings_cursor = 0;
+            self.state = GameState::Settings;
+            return;
+        }
+
+        // Gioca la caverna del giorno con T, una sola volta al giorno
+        if is_key_pressed(KeyCode::T) {
+            self.start_daily();
+            return;
+        }
+
+        // Mostra i punteggi migliori con H (o il tasto est)
+        if is_key_pressed(KeyCode::H) || self.controller.gamepad.is_pressed(GamepadButton::East) {
+            self.show_leaderboard(None);
+            return;
+        }
+
         // Passa allo stato di gioco se viene premuto un tasto o passa abbastanza tempo
         if is_key_pressed(KeyCode::Space)
             || is_mouse_button_pressed(MouseButton::Left)
+            || self.controller.gamepad.is_pressed(GamepadButton::Start)
+            || self.controller.gamepad.is_pressed(GamepadButton::South)
             || self.splash_timer > 3.0
         {
             self.state = GameState::Play;
@@ -92,62 +288,622 @@ impl Game {
     }
 
     fn update_play(&mut self) {
-        // Aggiorna il controller e il modello durante il gioco
-        self.controller.process_input_play(&mut self.model);
+        // Mentre un pipistrello trasporta il giocatore i comandi sono sospesi
+        if self.view.is_flying() {
+            return;
+        }
 
         // Se il gioco è finito, passa allo stato "Over"
         if self.model.game_over {
+            self.record_game();
+            self.finish_daily();
             self.state = GameState::Over;
             self.transition_timer = 0.0;
+
+            // Una vittoria da record chiede il nome del giocatore
+            if let Some(score) = leaderboard::score(&self.model)
+                && self
+                    .leaderboard
+                    .qualifies(self.model.difficulty, self.model.cave.kind(), score)
+            {
+                self.player_name = self.leaderboard.last_name.clone();
+                // Scarta i caratteri digitati durante la partita
+                while get_char_pressed().is_some() {}
+                self.state = GameState::EnterName;
+            }
+            return;
+        }
+
+        // Mostra o nascondi le stanze non ancora esplorate
+        if is_key_pressed(KeyCode::N) {
+            self.view.fog_of_war = !self.view.fog_of_war;
+            self.controller.view.fog_of_war = self.view.fog_of_war;
+        }
+
+        // Salva con F5, ricarica l'ultimo salvataggio con F9; la caverna
+        // del giorno non si salva, per non concedere altri tentativi, e in
+        // una partita valida non si torna indietro ricaricando
+        if is_key_pressed(KeyCode::F5) {
+            if self.daily.is_some() {
+                self.model.set_message(Message::new("daily.no_save"));
+            } else {
+                self.save_game();
+            }
+        }
+        if is_key_pressed(KeyCode::F9) {
+            if self.model.is_practice() {
+                self.load_game();
+            } else {
+                self.model
+                    .set_message(Message::new("game.reload_practice_only"));
+            }
+        }
+
+        // Aggiorna il controller e il modello durante il gioco
+        let logged = self.model.log().len();
+        self.controller.process_input_play(&mut self.model);
+
+        // Un'azione annullata non si sente di nuovo
+        if self.model.log().len() < logged {
+            self.audio.skip(&self.model);
+        }
+
+        // Anima il volo se la mossa ha incontrato dei pipistrelli
+        if self.model.log().len() > logged {
+            let flight = self.model.bat_flight();
+            if !flight.is_empty() {
+                self.view.start_bat_flight(flight);
+            }
         }
     }
 
     fn update_over(&mut self) {
         // Aggiorna il timer per la schermata di game over
-        self.transition_timer += get_frame_time();
+        self.transition_timer += get_frame_time() * self.settings.animation_speed;
+
+        let pad = &self.controller.gamepad;
 
         // Permetti di ricominciare premendo R
-        if is_key_pressed(KeyCode::R) {
+        if is_key_pressed(KeyCode::R)
+            || pad.is_pressed(GamepadButton::Start)
+            || pad.is_pressed(GamepadButton::South)
+        {
             self.model.reset();
+            self.daily = None;
             self.state = GameState::Play;
         }
 
+        // In allenamento Z riprende la partita prima dell'ultima azione
+        if is_key_pressed(KeyCode::Z) && self.model.is_practice() {
+            self.controller
+                .controls
+                .execute(&mut self.model, PlayCommand::Undo);
+            if !self.model.game_over {
+                self.audio.skip(&self.model);
+                self.state = GameState::Play;
+            }
+            return;
+        }
+
         // Permetti di tornare alla schermata iniziale premendo Esc
-        if is_key_pressed(KeyCode::Escape) {
+        if is_key_pressed(KeyCode::Escape) || pad.is_pressed(GamepadButton::East) {
             self.model.reset();
+            self.daily = None;
             self.state = GameState::Splash;
             self.splash_timer = 0.0;
         }
+
+        // Rivedi la partita appena giocata premendo V
+        if is_key_pressed(KeyCode::V) || pad.is_pressed(GamepadButton::North) {
+            self.replay = Replay::new(self.model.log(), self.model.layout);
+            if self.replay.is_some() {
+                self.state = GameState::Replay;
+            }
+        }
+
+        // Esporta il registro della partita premendo L
+        if is_key_pressed(KeyCode::L) {
+            self.export_log();
+        }
+
+        // Copia il risultato della caverna del giorno premendo C
+        if is_key_pressed(KeyCode::C)
+            && let Some(result) = self.daily.and_then(|date| self.daily_history.result(date))
+        {
+            miniquad::window::clipboard_set(result);
+            self.model.set_message(Message::new("daily.copied"));
+        }
+
+        // Mostra i punteggi migliori premendo H
+        if is_key_pressed(KeyCode::H) || self.controller.gamepad.is_pressed(GamepadButton::West) {
+            self.show_leaderboard(None);
+        }
+    }
+
+    fn update_enter_name(&mut self) {
+        self.transition_timer += get_frame_time() * self.settings.animation_speed;
+
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && self.player_name.chars().count() < MAX_NAME_LENGTH {
+                self.player_name.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.player_name.pop();
+        }
+
+        // Conferma il nome con Invio, oppure rinuncia al record con Esc
+        if is_key_pressed(KeyCode::Enter) && !self.player_name.trim().is_empty() {
+            if let Some(entry) = HighScore::new(&self.model, &self.player_name) {
+                let rank = self.leaderboard.insert(entry);
+                if let Err(err) = self.leaderboard.save(&leaderboard_path()) {
+                    self.model.message.extend([
+                        Message::new("leaderboard.save_failed"),
+                        Message::new("error.io").arg("error", err),
+                    ]);
+                }
+                self.show_leaderboard(rank);
+            }
+        } else if is_key_pressed(KeyCode::Escape) {
+            self.state = GameState::Over;
+        }
+    }
+
+    fn update_leaderboard(&mut self) {
+        let pad = &self.controller.gamepad;
+        let (difficulty, cave) = self.board;
+
+        // Scegli la tabella: difficoltà con su/giù, caverna con sinistra/destra
+        let board = if is_key_pressed(KeyCode::Up) || pad.is_pressed(GamepadButton::DPadUp) {
+            (difficulty.previous(), cave)
+        } else if is_key_pressed(KeyCode::Down) || pad.is_pressed(GamepadButton::DPadDown) {
+            (difficulty.next(), cave)
+        } else if is_key_pressed(KeyCode::Left) || pad.is_pressed(GamepadButton::DPadLeft) {
+            (difficulty, cave.previous())
+        } else if is_key_pressed(KeyCode::Right) || pad.is_pressed(GamepadButton::DPadRight) {
+            (difficulty, cave.next())
+        } else {
+            self.board
+        };
+        if board != self.board {
+            self.board = board;
+            self.board_highlight = None;
+        }
+
+        if is_key_pressed(KeyCode::Escape)
+            || is_key_pressed(KeyCode::H)
+            || pad.is_pressed(GamepadButton::East)
+        {
+            self.state = self.board_return;
+            self.splash_timer = 0.0;
+        }
+    }
+
+    // Mostra la tabella della partita corrente, evidenziando il nuovo record
+    fn show_leaderboard(&mut self, highlight: Option<usize>) {
+        self.board = (self.model.difficulty, self.model.cave.kind());
+        self.board_highlight = highlight;
+        self.board_return = match self.state {
+            GameState::Splash => GameState::Splash,
+            _ => GameState::Over,
+        };
+        self.state = GameState::Leaderboard;
+    }
+
+    fn update_replay(&mut self) {
+        let pad = &self.controller.gamepad;
+        let Some(replay) = self.replay.as_mut() else {
+            self.state = GameState::Over;
+            return;
+        };
+
+        if is_key_pressed(KeyCode::Right) || pad.is_pressed(GamepadButton::DPadRight) {
+            replay.step_forward();
+        } else if is_key_pressed(KeyCode::Left) || pad.is_pressed(GamepadButton::DPadLeft) {
+            replay.step_backward();
+        } else if is_key_pressed(KeyCode::Home) || pad.is_pressed(GamepadButton::DPadUp) {
+            replay.go_to(0);
+        } else if is_key_pressed(KeyCode::End) || pad.is_pressed(GamepadButton::DPadDown) {
+            replay.go_to(replay.len());
+        }
+
+        // Torna alla schermata di fine partita
+        if is_key_pressed(KeyCode::Escape) || pad.is_pressed(GamepadButton::East) {
+            self.replay = None;
+            self.state = GameState::Over;
+        }
+    }
+
+    fn update_stats(&mut self) {
+        let pad = &self.controller.gamepad;
+        if is_key_pressed(KeyCode::Escape)
+            || is_key_pressed(KeyCode::S)
+            || pad.is_pressed(GamepadButton::East)
+        {
+            self.state = GameState::Splash;
+            self.splash_timer = 0.0;
+        }
+    }
+
+    fn update_settings(&mut self) {
+        let pad = &self.controller.gamepad;
+        let count = Setting::ALL.len();
+        if is_key_pressed(KeyCode::Down) || pad.is_pressed(GamepadButton::DPadDown) {
+            self.settings_cursor = (self.settings_cursor + 1) % count;
+        } else if is_key_pressed(KeyCode::Up) || pad.is_pressed(GamepadButton::DPadUp) {
+            self.settings_cursor = (self.settings_cursor + count - 1) % count;
+        }
+
+        let forward = is_key_pressed(KeyCode::Right)
+            || is_key_pressed(KeyCode::Enter)
+            || pad.is_pressed(GamepadButton::DPadRight)
+            || pad.is_pressed(GamepadButton::South);
+        let back = is_key_pressed(KeyCode::Left) || pad.is_pressed(GamepadButton::DPadLeft);
+        if forward || back {
+            let settings = &mut self.settings;
+            match Setting::ALL[self.settings_cursor] {
+                Setting::Language => {
+                    let current = settings.language;
+                    let language = current.unwrap_or(self.view.translations.language());
+                    settings.language = Some(language.next());
+                }
+                Setting::Difficulty if back => settings.difficulty = settings.difficulty.previous(),
+                Setting::Difficulty => settings.difficulty = settings.difficulty.next(),
+                Setting::Practice => settings.practice = !settings.practice,
+                Setting::Cave if back => settings.cave = settings.cave.previous(),
+                Setting::Cave => settings.cave = settings.cave.next(),
+                Setting::Layout => settings.layout = settings.layout.next(),
+                Setting::Volume => {
+                    let step = if back { -VOLUME_STEP } else { VOLUME_STEP };
+                    settings.volume = (settings.volume + step).clamp(0.0, 1.0);
+                }
+                Setting::Sound => settings.muted = !settings.muted,
+                Setting::Colorblind => settings.colorblind = !settings.colorblind,
+                Setting::WindowSize => {
+                    let (width, height) = settings.next_window_size();
+                    (settings.window_width, settings.window_height) = (width, height);
+                    settings.fullscreen = false;
+                    set_fullscreen(false);
+                    request_new_screen_size(width as f32, height as f32);
+                }
+                Setting::Fullscreen => {
+                    settings.fullscreen = !settings.fullscreen;
+                    set_fullscreen(settings.fullscreen);
+                }
+                Setting::AnimationSpeed => {
+                    settings.animation_speed = settings.next_animation_speed();
+                }
+            }
+            // Ogni modifica ha effetto subito
+            self.apply_settings();
+        }
+
+        // Torna alla schermata iniziale conservando le impostazioni
+        if is_key_pressed(KeyCode::Escape)
+            || is_key_pressed(KeyCode::O)
+            || self.controller.gamepad.is_pressed(GamepadButton::East)
+        {
+            self.save_settings();
+            self.state = GameState::Splash;
+            self.splash_timer = 0.0;
+        }
+    }
+
+    // Porta le impostazioni nel gioco: lingua, opzioni della prossima
+    // partita, audio, colori e animazioni. La finestra cambia solo quando
+    // la si sceglie, per non annullare il ridimensionamento a mano
+    fn apply_settings(&mut self) {
+        let settings = &self.settings;
+        if let Some(language) = settings.language
+            && language != self.view.translations.language()
+        {
+            self.view.translations = load_translations(language);
+        }
+
+        // Le opzioni della partita cambiano solo prima di cominciarla
+        if self.state != GameState::Play {
+            if self.model.cave.kind() != settings.cave {
+                self.model.set_cave_kind(settings.cave);
+            }
+            if self.model.layout != settings.layout {
+                self.model.set_layout(settings.layout);
+            }
+            if self.model.difficulty != settings.difficulty {
+                self.model.set_difficulty(settings.difficulty);
+            }
+            if self.model.is_practice() != settings.practice {
+                self.model.set_practice(settings.practice);
+            }
+        }
+
+        self.audio.volume = settings.volume;
+        self.audio.muted = settings.muted;
+        self.view.palette = if settings.colorblind {
+            Palette::COLORBLIND
+        } else {
+            Palette::STANDARD
+        };
+        self.view.animation_speed = settings.animation_speed;
+    }
+
+    fn save_settings(&mut self) {
+        if let Err(err) = self.settings.save(&settings_path()) {
+            self.model
+                .set_message(Message::new("settings.save_failed").arg("error", err));
+        }
+    }
+
+    // Aggiunge la partita appena conclusa alle statistiche del giocatore e
+    // scarta il salvataggio, che non si potrà più riprendere per rigiocarla
+    fn record_game(&mut self) {
+        if let Err(err) = save::discard(&save_path()) {
+            eprintln!("Salvataggio non eliminato: {}", err);
+        }
+        self.profile.record(&self.model);
+        if let Err(err) = self.profile.save(&profile_path()) {
+            self.model.message.extend([
+                Message::new("profile.save_failed"),
+                Message::new("error.io").arg("error", err),
+            ]);
+        }
+    }
+
+    // Avvia la caverna del giorno, se oggi non è già stata giocata
+    fn start_daily(&mut self) {
+        let date = Date::today();
+        if !self.daily_history.start(date) {
+            return;
+        }
+        if let Err(err) = self.daily_history.save(&daily_path()) {
+            eprintln!("Caverne del giorno non salvate: {}", err);
+        }
+
+        self.model = daily::daily_game(date, Arc::new(SystemClock::new()));
+        self.model
+            .set_message(Message::new("daily.started").arg("date", date));
+        self.controller.reset();
+        self.daily = Some(date);
+        self.state = GameState::Play;
+    }
+
+    // Conserva il risultato della caverna del giorno appena conclusa
+    fn finish_daily(&mut self) {
+        let Some(date) = self.daily else {
+            return;
+        };
+        if let Some(result) = daily::share(&self.model, date) {
+            self.daily_history.finish(date, result);
+            if let Err(err) = self.daily_history.save(&daily_path()) {
+                eprintln!("Caverne del giorno non salvate: {}", err);
+            }
+        }
+    }
+
+    fn export_log(&mut self) {
+        let path = data_dir().join(format!("log-{}.json", self.model.seed()));
+        let message = match events::export_log(self.model.log(), &path) {
+            Ok(()) => Message::new("log.exported").arg("path", path.display()),
+            Err(err) => Message::new("log.export_failed").arg("error", err),
+        };
+        self.model.set_message(message);
+    }
+
+    fn save_game(&mut self) {
+        match save::save_to_file(&self.model, &save_path()) {
+            Ok(()) => self.model.set_message(Message::new("game.saved")),
+            Err(err) => {
+                self.model.message = vec![Message::new("game.save_failed"), err.message()];
+            }
+        }
+    }
+
+    // Carica la partita salvata e passa allo stato corrispondente
+    fn load_game(&mut self) -> bool {
+        match save::resume_from_file(&save_path(), Arc::new(SystemClock::new())) {
+            Ok(model) => {
+                self.model = model;
+                self.model.set_message(Message::new("game.loaded"));
+                self.daily = None;
+                self.audio.skip(&self.model);
+                self.controller.reset();
+                self.state = if self.model.game_over {
+                    GameState::Over
+                } else {
+                    GameState::Play
+                };
+                self.transition_timer = 0.0;
+                true
+            }
+            Err(err) => {
+                self.model.message = vec![Message::new("game.load_failed"), err.message()];
+                false
+            }
+        }
+    }
+
+    // Salva automaticamente la partita in corso alla chiusura della finestra
+    fn on_quit(&mut self) {
+        if self.state == GameState::Play && !self.model.game_over && self.daily.is_none() {
+            self.save_game();
+        }
+
+        // La prossima sessione riapre la finestra delle dimensioni lasciate
+        if !self.settings.fullscreen {
+            self.settings.window_width = screen_width().round() as u32;
+            self.settings.window_height = screen_height().round() as u32;
+            self.save_settings();
+        }
     }
 
     fn draw(&self) {
+        // Il gioco è disegnato su uno schermo virtuale adattato alla finestra
+        set_camera(&game_view::screen_camera());
+
         match self.state {
-            GameState::Splash => self.view.draw_splash(),
-            GameState::Play => self.view.draw_game(&self.model),
-            GameState::Over => self.view.draw_game_over(&self.model, self.transition_timer),
+            GameState::Splash => self.view.draw_splash(
+                self.model.cave.kind(),
+                self.model.layout,
+                self.model.difficulty,
+                self.model.is_practice(),
+                save_path().exists(),
+                (Date::today(), self.daily_history.has_played(Date::today())),
+            ),
+            GameState::Play => {
+                self.view.draw_game(&self.model);
+                self.view.draw_exit_hints(
+                    &self.model,
+                    self.controller.exits(&self.model),
+                    self.controller.focused_room(&self.model),
+                );
+                let controls = &self.controller.controls;
+                if controls.shoot_mode {
+                    self.view.draw_arrow_path(&self.model, &controls.arrow_path);
+                }
+            }
+            GameState::Over => {
+                self.view.draw_game_over(&self.model, self.transition_timer);
+                if let Some(result) = self.daily.and_then(|date| self.daily_history.result(date)) {
+                    self.view.draw_daily_result(result);
+                }
+            }
+            GameState::Replay => {
+                if let Some(replay) = &self.replay {
+                    self.view.draw_replay(replay);
+                }
+            }
+            GameState::Stats => self.view.draw_stats(&self.profile),
+            GameState::EnterName => {
+                self.view.draw_game_over(&self.model, self.transition_timer);
+                self.view.draw_name_entry(&self.model, &self.player_name);
+            }
+            GameState::Leaderboard => {
+                let (difficulty, cave) = self.board;
+                self.view.draw_leaderboard(
+                    &self.leaderboard.table(difficulty, cave),
+                    self.board,
+                    self.board_highlight,
+                );
+            }
+            GameState::Settings => self
+                .view
+                .draw_settings(&self.settings, self.settings_cursor),
+        }
+    }
+}
+
+/// Directory for the game's files, inside the user's data directory when
+/// available.
+fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustedbytes-wumpus")
+}
+
+/// Texts of `language`, with any translation files in the data directory
+/// taking precedence over the built-in ones.
+fn load_translations(language: Language) -> Translations {
+    let dir = data_dir().join("lang");
+    Translations::with_overrides(language, &dir).unwrap_or_else(|err| {
+        eprintln!("Traduzioni in {} ignorate: {}", dir.display(), err);
+        Translations::new(language)
+    })
+}
+
+/// Location of the settings, inside the user's configuration directory when
+/// available.
+fn settings_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustedbytes-wumpus")
+        .join(SETTINGS_FILE_NAME)
+}
+
+/// Location of the save file.
+fn save_path() -> PathBuf {
+    data_dir().join(SAVE_FILE_NAME)
+}
+
+/// Location of the player's statistics.
+fn profile_path() -> PathBuf {
+    data_dir().join(PROFILE_FILE_NAME)
+}
+
+/// Location of the high scores.
+fn leaderboard_path() -> PathBuf {
+    data_dir().join(LEADERBOARD_FILE_NAME)
+}
+
+/// Location of the daily caves already played.
+fn daily_path() -> PathBuf {
+    data_dir().join(DAILY_FILE_NAME)
+}
+
+/// Reads the optional `--seed <n>` (or `--seed=<n>`) command line argument.
+fn parse_seed_arg() -> Option<u64> {
+    let value = find_arg("--seed")?;
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            eprintln!("Seme non valido: {}", value);
+            None
+        }
+    }
+}
+
+/// Language from the optional `--lang <code>` argument.
+fn parse_language_arg() -> Option<Language> {
+    let code = find_arg("--lang")?;
+    let language = Language::from_code(&code);
+    if language.is_none() {
+        eprintln!("Lingua non disponibile: {}", code);
+    }
+    language
+}
+
+/// Settings saved by the player, or the default ones if they cannot be
+/// read.
+fn load_settings() -> Settings {
+    Settings::load(&settings_path()).unwrap_or_else(|err| {
+        eprintln!("Impostazioni illeggibili: {}", err);
+        Settings::default()
+    })
+}
+
+/// Value of a `--name <value>` or `--name=<value>` command line argument.
+fn find_arg(name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
         }
     }
+    None
 }
 
 /// Macroquad window configuration.
 /// This function is called at startup to set the window parameters.
 fn window_conf() -> Conf {
+    let settings = load_settings();
     Conf {
         // Window title
         window_title: "RustedBytes - Hunt the Wumpus".to_string(),
         // Desired window dimensions in pixels
-        window_width: SCREEN_WIDTH as i32,
-        window_height: SCREEN_HEIGHT as i32,
-        // Enable support for high pixel density displays (Retina, HiDPI).
-        // Important for rendering correctly at the specified resolution.
-        high_dpi: false,
+        window_width: settings.window_width as i32,
+        window_height: settings.window_height as i32,
+        // Enable support for high pixel density displays (Retina, HiDPI):
+        // the window keeps its size in points and is drawn with all of its
+        // pixels, the camera scales everything to fit.
+        high_dpi: true,
         // Enable Multi-Sample Anti-Aliasing (MSAA) to smooth edges.
         // Common values are 2, 4, 8. Higher values improve quality
         // but require more GPU resources. 4 is a good compromise.
         sample_count: 4,
-        window_resizable: false, // Disable window resizing
-        // Additional options (e.g., fullscreen: false)
-        // can be added here if needed.
+        window_resizable: true,
+        fullscreen: settings.fullscreen,
         ..Default::default() // Use default values for unspecified options
     }
 }
@@ -155,10 +911,18 @@ fn window_conf() -> Conf {
 // ----- MAIN -----
 #[macroquad::main(window_conf)]
 async fn main() {
-    let mut game = Game::new();
+    let mut game = Game::new(parse_seed_arg(), parse_language_arg(), load_settings());
     game.initialize().await;
 
+    // Gestiamo noi la chiusura per poter salvare la partita
+    prevent_quit();
+
     loop {
+        if is_quit_requested() {
+            game.on_quit();
+            break;
+        }
+
         // Aggiornamento e disegno gestiti attraverso i diversi stati
         game.update();
         game.draw();
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use crate::difficulty::Difficulty;
+use crate::game_model::GameModel;
+use crate::stats::Outcome;
+
+/// Games lost, by what ended them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Losses {
+    pub eaten_by_wumpus: u32,
+    pub fell_in_pit: u32,
+    pub shot_self: u32,
+    pub out_of_arrows: u32,
+}
+
+impl Losses {
+    pub fn total(&self) -> u32 {
+        self.eaten_by_wumpus + self.fell_in_pit + self.shot_self + self.out_of_arrows
+    }
+}
+
+/// Record of every game finished on this computer.
+///
+/// Fields missing from the file, e.g. written by an older version, start
+/// from zero.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub games: u32,
+    pub wins: u32,
+    pub losses: Losses,
+    /// Wins in a row up to the last game.
+    pub current_streak: u32,
+    pub best_streak: u32,
+    /// Shortest winning time in seconds, for each difficulty won at least
+    /// once.
+    pub best_times: BTreeMap<Difficulty, f64>,
+    pub moves: u64,
+    pub shots: u64,
+    pub bat_rides: u64,
+}
+
+impl Profile {
+    /// Adds a finished game; does nothing while it is still going, or for
+    /// practice games.
+    pub fn record(&mut self, model: &GameModel) {
+        if model.is_practice() {
+            return;
+        }
+        let Some(outcome) = model.outcome() else {
+            return;
+        };
+        let stats = model.stats();
+
+        self.games += 1;
+        self.moves += stats.moves as u64;
+        self.shots += stats.shots as u64;
+        self.bat_rides += stats.bat_rides as u64;
+
+        match outcome {
+            Outcome::Won => {
+                self.wins += 1;
+                self.current_streak += 1;
+                self.best_streak = self.best_streak.max(self.current_streak);
+                let best = self
+                    .best_times
+                    .entry(model.difficulty)
+                    .or_insert(stats.time);
+                *best = best.min(stats.time);
+            }
+            Outcome::EatenByWumpus => self.losses.eaten_by_wumpus += 1,
+            Outcome::FellInPit => self.losses.fell_in_pit += 1,
+            Outcome::ShotSelf => self.losses.shot_self += 1,
+            Outcome::OutOfArrows => self.losses.out_of_arrows += 1,
+        }
+        if outcome != Outcome::Won {
+            self.current_streak = 0;
+        }
+    }
+
+    /// Percentage of the games won, 0 before the first game.
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.wins as f64 * 100.0 / self.games as f64
+        }
+    }
+
+    /// Reads the profile from `path`; a missing file is a new profile.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Profile::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the profile to `path`, creating the parent directories if
+    /// needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use crate::action::Action;
+use crate::clock::ManualClock;
+use crate::events::{GameEvent, LogEntry};
+use crate::game_model::GameModel;
+use crate::layout::LayoutStrategy;
+
+/// Steps through a recorded game.
+///
+/// Games are fully determined by their seed and the player's actions, so the
+/// replay rebuilds the state at any step by playing the recorded actions
+/// again on a fresh model. The result is a real `GameModel`, drawn like any
+/// other game.
+pub struct Replay {
+    log: Vec<LogEntry>,
+    // Index in `log` of every player action
+    actions: Vec<usize>,
+    layout: LayoutStrategy,
+    step: usize,
+    model: GameModel,
+    // Set to the recorded time of each action before playing it
+    clock: Arc<ManualClock>,
+}
+
+impl Replay {
+    /// Prepares the replay of `log`, positioned before the first action.
+    /// Returns None if the log does not start with `GameStarted`.
+    pub fn new(log: &[LogEntry], layout: LayoutStrategy) -> Option<Self> {
+        let GameEvent::GameStarted {
+            seed,
+            cave,
+            difficulty,
+            ..
+        } = log.first()?.event
+        else {
+            return None;
+        };
+
+        let actions = log
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.event.is_player_action())
+            .map(|(i, _)| i)
+            .collect();
+
+        let clock = Arc::new(ManualClock::default());
+        let mut model = GameModel::with_seed_and_clock(seed, clock.clone());
+        model.difficulty = difficulty;
+        model.set_cave_kind(cave);
+        model.set_layout(layout);
+
+        Some(Replay {
+            log: log.to_vec(),
+            actions,
+            layout,
+            step: 0,
+            model,
+            clock,
+        })
+    }
+
+    /// Game state after the current step.
+    pub fn model(&self) -> &GameModel {
+        &self.model
+    }
+
+    /// Number of player actions played so far.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Total number of player actions in the recording.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Rooms where the bats and the pits were placed when the game started.
+    pub fn hazards(&self) -> (&[usize], &[usize]) {
+        match &self.log[0].event {
+            GameEvent::GameStarted { bats, pits, .. } => (bats, pits),
+            _ => (&[], &[]),
+        }
+    }
+
+    /// Events caused by the last action played, or the start of the game.
+    pub fn current_events(&self) -> &[LogEntry] {
+        let start = match self.step {
+            0 => 0,
+            step => self.actions[step - 1],
+        };
+        let end = self
+            .actions
+            .get(self.step)
+            .copied()
+            .unwrap_or(self.log.len());
+        &self.log[start..end]
+    }
+
+    pub fn step_forward(&mut self) {
+        if self.step < self.actions.len() {
+            self.play(self.step);
+            self.step += 1;
+        }
+    }
+
+    pub fn step_backward(&mut self) {
+        if self.step > 0 {
+            self.go_to(self.step - 1);
+        }
+    }
+
+    /// Jumps to the state after `step` actions.
+    pub fn go_to(&mut self, step: usize) {
+        let step = step.min(self.actions.len());
+        if step < self.step {
+            // Rebuilt from the start: the model cannot be rewound
+            if let Some(replay) = Replay::new(&self.log, self.layout) {
+                *self = replay;
+            }
+        }
+        while self.step < step {
+            self.step_forward();
+        }
+    }
+
+    fn play(&mut self, step: usize) {
+        let entry = &self.log[self.actions[step]];
+        self.clock.set(entry.time);
+        // The recorded actions were accepted when played, and the game
+        // plays out the same again, so none of them can be refused
+        if let Some(action) = Action::from_event(&entry.event) {
+            let _ = self.model.apply(action);
+        }
+    }
+}
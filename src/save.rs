@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path, sync::Arc};
+
+use crate::cave_map::CaveMap;
+use crate::clock::Clock;
+use crate::difficulty::Difficulty;
+use crate::events::LogEntry;
+use crate::game_model::{GameModel, GameRng};
+use crate::i18n::Message;
+use crate::layout::LayoutStrategy;
+use crate::topology::CaveTopology;
+
+/// Version written in every save. Bump it whenever `SaveGame` changes in a
+/// way older builds cannot read.
+pub const SAVE_VERSION: u32 = 2;
+
+/// Snapshot of a game in progress, including the hidden hazards and the
+/// generator state, so a loaded game continues exactly as it would have.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub seed: u64,
+    pub cave: CaveTopology,
+    pub layout: LayoutStrategy,
+    pub player_position: usize,
+    pub wumpus_position: usize,
+    pub bat_positions: Vec<usize>,
+    pub pit_positions: Vec<usize>,
+    pub arrows: u32,
+    pub moves_count: u32,
+    /// Seconds played so far.
+    pub elapsed_time: f64,
+    pub game_over: bool,
+    pub win: bool,
+    /// Saves made before difficulty levels existed are classic games.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub turns: u32,
+    pub message: Vec<Message>,
+    pub map: CaveMap,
+    /// Older saves carry no log; their games just cannot be replayed.
+    #[serde(default)]
+    pub log: Vec<LogEntry>,
+    pub rng: GameRng,
+    /// Practice games stay practice games once loaded.
+    #[serde(default)]
+    pub practice: bool,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    UnsupportedVersion(u32),
+    /// The file parsed but describes an impossible game.
+    Corrupted(&'static str),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "errore di accesso al file: {}", err),
+            SaveError::Format(err) => write!(f, "salvataggio illeggibile: {}", err),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "versione del salvataggio non supportata: {}", version)
+            }
+            SaveError::Corrupted(reason) => write!(f, "salvataggio corrotto: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(err) => Some(err),
+            SaveError::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl SaveError {
+    /// Description of the error for the player.
+    pub fn message(&self) -> Message {
+        match self {
+            SaveError::Io(err) => Message::new("error.io").arg("error", err),
+            SaveError::Format(err) => Message::new("error.format").arg("error", err),
+            SaveError::UnsupportedVersion(version) => {
+                Message::new("error.version").arg("version", version)
+            }
+            SaveError::Corrupted(_) => Message::new("error.corrupted"),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(err: serde_json::Error) -> Self {
+        SaveError::Format(err)
+    }
+}
+
+impl SaveGame {
+    pub fn to_json(&self) -> Result<String, SaveError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SaveError> {
+        // Check the version first, so that a newer format is reported as
+        // such rather than as a parse error
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+        let header: Header = serde_json::from_str(json)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(header.version));
+        }
+
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// Writes the game to `path`, creating the parent directories if needed.
+pub fn save_to_file(model: &GameModel, path: &Path) -> Result<(), SaveError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, model.to_save().to_json()?)?;
+    Ok(())
+}
+
+/// Reads a game saved with [`save_to_file`]; its clock resumes from the
+/// time already played.
+pub fn load_from_file(path: &Path, clock: Arc<dyn Clock>) -> Result<GameModel, SaveError> {
+    let json = fs::read_to_string(path)?;
+    GameModel::from_save(SaveGame::from_json(&json)?, clock)
+}
+
+/// Loads a saved game and deletes the save, so that each saved game can be
+/// resumed, and recorded once finished, only once.
+pub fn resume_from_file(path: &Path, clock: Arc<dyn Clock>) -> Result<GameModel, SaveError> {
+    let model = load_from_file(path, clock)?;
+    fs::remove_file(path)?;
+    Ok(model)
+}
+
+/// Deletes the save at `path`, if there is one.
+pub fn discard(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+    use crate::clock::ManualClock;
+    use serde_json::{Value, json};
+
+    fn clock() -> Arc<dyn Clock> {
+        Arc::new(ManualClock::new(0.0))
+    }
+
+    // A game a few moves in
+    fn game() -> GameModel {
+        let mut model = GameModel::with_seed_and_clock(7, clock());
+        for _ in 0..3 {
+            let exit = model.cave.exits(model.player_position)[0];
+            if model.apply(Action::Move(exit)).is_err() {
+                break;
+            }
+        }
+        model
+    }
+
+    fn load(json: &str) -> Result<GameModel, SaveError> {
+        GameModel::from_save(SaveGame::from_json(json)?, clock())
+    }
+
+    // The save of `model` as JSON, changed by `edit`
+    fn edited(model: &GameModel, edit: impl FnOnce(&mut Value)) -> String {
+        let mut value = serde_json::to_value(model.to_save()).unwrap();
+        edit(&mut value);
+        value.to_string()
+    }
+
+    #[test]
+    fn saves_round_trip() {
+        let mut model = game();
+        let json = model.to_save().to_json().unwrap();
+        let mut loaded = load(&json).unwrap();
+        assert_eq!(loaded.to_save().to_json().unwrap(), json);
+
+        // The generator carries on where it was, so both games go on alike
+        for model in [&mut model, &mut loaded] {
+            let exit = model.cave.exits(model.player_position)[1];
+            let _ = model.apply(Action::Move(exit));
+        }
+        assert_eq!(model.log(), loaded.log());
+    }
+
+    #[test]
+    fn unknown_versions_are_refused() {
+        let model = game();
+        let json = edited(&model, |save| save["version"] = json!(SAVE_VERSION + 1));
+        assert!(matches!(
+            load(&json),
+            Err(SaveError::UnsupportedVersion(version)) if version == SAVE_VERSION + 1
+        ));
+
+        let mut save = model.to_save();
+        save.version = 0;
+        assert!(matches!(
+            GameModel::from_save(save, clock()),
+            Err(SaveError::UnsupportedVersion(0))
+        ));
+    }
+
+    #[test]
+    fn unreadable_saves_are_refused() {
+        assert!(matches!(load("not a save"), Err(SaveError::Format(_))));
+        let json = edited(&game(), |save| save["arrows"] = json!("many"));
+        assert!(matches!(load(&json), Err(SaveError::Format(_))));
+    }
+
+    #[test]
+    fn corrupted_saves_are_refused() {
+        let model = game();
+        let pairs: Vec<[usize; 1]> = (0..20).map(|room| [room ^ 1]).collect();
+        let mut one_way: Vec<Vec<usize>> = (0..20)
+            .map(|room| model.cave.exits(room).to_vec())
+            .collect();
+        one_way[0].push(2);
+        let edits = [
+            ("/player_position", json!(20)),
+            ("/pit_positions", json!([3, 99])),
+            ("/cave/tunnels/0", json!([1, 4, 25])),
+            ("/cave/tunnels", json!(pairs)),
+            ("/cave/tunnels", json!(one_way)),
+        ];
+        for (field, value) in edits {
+            let json = edited(&model, |save| *save.pointer_mut(field).unwrap() = value);
+            assert!(
+                matches!(load(&json), Err(SaveError::Corrupted(_))),
+                "{}",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn resumed_saves_are_used_up() {
+        let dir = std::env::temp_dir().join(format!("wumpus-save-{}", std::process::id()));
+        let path = dir.join("save.json");
+        let model = game();
+        save_to_file(&model, &path).unwrap();
+
+        let loaded = resume_from_file(&path, clock()).unwrap();
+        assert_eq!(
+            loaded.to_save().to_json().unwrap(),
+            model.to_save().to_json().unwrap()
+        );
+        assert!(!path.exists());
+        assert!(matches!(
+            resume_from_file(&path, clock()),
+            Err(SaveError::Io(_))
+        ));
+        discard(&path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
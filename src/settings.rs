@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+use crate::difficulty::Difficulty;
+use crate::i18n::Language;
+use crate::layout::LayoutStrategy;
+use crate::topology::CaveKind;
+
+/// Window sizes offered by the settings, all with the proportions of the
+/// virtual screen the game is drawn on. The window can also be resized
+/// freely.
+pub const WINDOW_SIZES: [(u32, u32); 4] = [(800, 600), (1024, 768), (1280, 960), (1600, 1200)];
+
+/// Speeds offered for the animations, as multiples of the normal one.
+pub const ANIMATION_SPEEDS: [f32; 4] = [0.5, 1.0, 1.5, 2.0];
+
+/// Preferences of the player, kept between sessions.
+///
+/// Settings missing from the file, e.g. written by an older version or by
+/// hand, take their default value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// `None` follows the language of the system.
+    pub language: Option<Language>,
+    pub difficulty: Difficulty,
+    /// Games where actions can be taken back, without scores.
+    pub practice: bool,
+    pub cave: CaveKind,
+    pub layout: LayoutStrategy,
+    /// From 0 (silent) to 1.
+    pub volume: f32,
+    pub muted: bool,
+    /// Tells clues and hazards apart by more than hue.
+    pub colorblind: bool,
+    pub window_width: u32,
+    pub window_height: u32,
+    pub fullscreen: bool,
+    /// Multiple of the normal speed of the animations.
+    pub animation_speed: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let (window_width, window_height) = WINDOW_SIZES[0];
+        Settings {
+            language: None,
+            difficulty: Difficulty::default(),
+            practice: false,
+            cave: CaveKind::Dodecahedron,
+            layout: LayoutStrategy::ConcentricRings,
+            volume: 1.0,
+            muted: false,
+            colorblind: false,
+            window_width,
+            window_height,
+            fullscreen: false,
+            animation_speed: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    /// The smallest window size offered that is wider than the current
+    /// one, starting over after the largest.
+    pub fn next_window_size(&self) -> (u32, u32) {
+        WINDOW_SIZES
+            .into_iter()
+            .find(|&(width, _)| width > self.window_width)
+            .unwrap_or(WINDOW_SIZES[0])
+    }
+
+    /// The next animation speed offered after the current one, starting
+    /// over after the fastest.
+    pub fn next_animation_speed(&self) -> f32 {
+        let index = ANIMATION_SPEEDS
+            .iter()
+            .position(|&speed| speed == self.animation_speed);
+        ANIMATION_SPEEDS[index.map_or(1, |i| (i + 1) % ANIMATION_SPEEDS.len())]
+    }
+
+    /// Reads the settings from the TOML file at `path`; a missing file gives
+    /// the default settings.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Settings::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the settings to `path` as TOML, creating the parent
+    /// directories if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = toml::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, text)
+    }
+}
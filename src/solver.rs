@@ -0,0 +1,472 @@
+use std::collections::VecDeque;
+
+use crate::action::Action;
+use crate::agent::{Agent, Percept};
+use crate::cave_map::Clues;
+use crate::difficulty::Difficulty;
+use crate::game_model::{GameModel, MAX_ARROW_PATH};
+use crate::i18n::Message;
+
+// Chance of a pit in a room nothing is known about
+const PIT_PRIOR: f64 = 0.2;
+
+/// An action chosen by the [`Solver`], and whether it was forced by logic or
+/// is a gamble.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub action: Action,
+    /// The move is provably safe, or the shot cannot miss the wumpus.
+    /// Never claimed where the wumpus wanders, since it may move in or out
+    /// of a room at the end of the turn.
+    pub certain: bool,
+}
+
+impl Decision {
+    /// The decision as a hint for the player.
+    pub fn message(&self) -> Message {
+        match (&self.action, self.certain) {
+            (Action::Move(room), true) => Message::new("hint.safe_move").arg("room", room),
+            (Action::Move(room), false) => Message::new("hint.risky_move").arg("room", room),
+            (Action::Shoot(path), certain) => {
+                let key = if certain {
+                    "hint.shoot"
+                } else {
+                    "hint.risky_shot"
+                };
+                let rooms = path
+                    .iter()
+                    .map(|room| room.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Message::new(key)
+                    .arg("room", path.last().copied().unwrap_or_default())
+                    .arg("path", rooms)
+            }
+        }
+    }
+}
+
+/// Reference player that reasons like a careful human.
+///
+/// It keeps a knowledge base of the rooms it has visited: their exits and
+/// the clues sensed there. From it, it tells which rooms are provably free
+/// of pits and of the wumpus, and where the wumpus must be once the stench
+/// leaves a single candidate. It always takes a safe room when there is
+/// one, shoots when the wumpus is located, and only gambles, on the least
+/// risky room, when logic runs out.
+///
+/// The wumpus may move after a missed shot, and the bats after carrying the
+/// player, so the clues about them only count from then on. On the
+/// difficulties where the wumpus also wanders on its own, the old clues
+/// still guide the solver until they contradict each other, but none of its
+/// decisions is certain.
+#[derive(Debug, Clone, Default)]
+pub struct Solver {
+    // Whether the wumpus wanders on its own, so that no clue about it holds
+    // for certain
+    wandering: bool,
+    // Exits and clues of every room visited so far
+    exits: Vec<Option<Vec<usize>>>,
+    clues: Vec<Option<Clues>>,
+    // Visited rooms whose clues still tell where the wumpus and the bats are
+    wumpus_clues: Vec<usize>,
+    bat_clues: Vec<usize>,
+    // Last action taken, to tell what came of it
+    last: Option<Action>,
+}
+
+impl Solver {
+    /// A solver for games on `difficulty`, whose rules tell whether the
+    /// wumpus wanders.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Solver {
+            wandering: difficulty.rules().wander_every.is_some(),
+            ..Solver::default()
+        }
+    }
+
+    // Forgets everything learned, for a cave with `num_rooms` rooms
+    fn clear(&mut self, num_rooms: usize) {
+        *self = Solver {
+            exits: vec![None; num_rooms],
+            clues: vec![None; num_rooms],
+            wandering: self.wandering,
+            ..Solver::default()
+        };
+    }
+
+    /// Best action for the player of `model`, worked out from what they
+    /// know: the rooms on their map and the clues sensed there. `None` once
+    /// the game is over.
+    pub fn hint(model: &GameModel) -> Option<Decision> {
+        if model.game_over {
+            return None;
+        }
+        let mut solver = Solver::new(model.difficulty);
+        solver.clear(model.cave.num_rooms());
+        for room in model.map.visited_rooms() {
+            let clues = model.map.clues(room).unwrap_or_default();
+            solver.learn(room, model.cave.exits(room).to_vec(), clues);
+        }
+        Some(solver.decide(model.player_position, model.arrows))
+    }
+
+    // Adds what the percept tells, after checking how the last action went
+    fn observe(&mut self, percept: &Percept) {
+        if self.exits.len() != percept.num_rooms {
+            self.clear(percept.num_rooms);
+        }
+
+        if !percept.bump {
+            match self.last {
+                // Landing elsewhere means bats carried the player off, and
+                // may have flown to another room
+                Some(Action::Move(to)) if to != percept.room => self.bat_clues.clear(),
+                // A missed shot may have woken the wumpus
+                Some(Action::Shoot(_)) => self.wumpus_clues.clear(),
+                _ => {}
+            }
+        }
+
+        let clues = Clues {
+            stench: percept.stench,
+            breeze: percept.breeze,
+            squeak: percept.squeak,
+        };
+        self.learn(percept.room, percept.exits.clone(), clues);
+    }
+
+    fn learn(&mut self, room: usize, exits: Vec<usize>, clues: Clues) {
+        self.exits[room] = Some(exits);
+        self.clues[room] = Some(clues);
+        for known in [&mut self.wumpus_clues, &mut self.bat_clues] {
+            if !known.contains(&room) {
+                known.push(room);
+            }
+        }
+    }
+
+    fn is_visited(&self, room: usize) -> bool {
+        self.clues[room].is_some()
+    }
+
+    // Visited rooms with their exits and clues
+    fn visited(&self) -> impl Iterator<Item = (usize, &[usize], Clues)> + '_ {
+        self.exits
+            .iter()
+            .zip(&self.clues)
+            .enumerate()
+            .filter_map(|(room, (exits, clues))| Some((room, exits.as_deref()?, (*clues)?)))
+    }
+
+    // No pit: the room was visited, or is next to a room without a breeze
+    fn is_pit_free(&self, room: usize) -> bool {
+        self.is_visited(room)
+            || self
+                .visited()
+                .any(|(_, exits, clues)| !clues.breeze && exits.contains(&room))
+    }
+
+    // Chance of a pit in `room`: certain when it is the only room left to
+    // explain a breeze, an even share of the breeze otherwise
+    fn pit_chance(&self, room: usize) -> f64 {
+        if self.is_pit_free(room) {
+            return 0.0;
+        }
+        self.visited()
+            .filter(|(_, exits, clues)| clues.breeze && exits.contains(&room))
+            .map(|(_, exits, _)| {
+                let suspects = exits.iter().filter(|&&exit| !self.is_pit_free(exit));
+                1.0 / suspects.count() as f64
+            })
+            .fold(PIT_PRIOR, f64::max)
+    }
+
+    // Rooms the wumpus can be in, given the clues that still count. If they
+    // contradict each other, e.g. because the wumpus wandered, only the
+    // clues of the current room are kept
+    fn wumpus_candidates(&mut self, room: usize) -> Vec<usize> {
+        let candidates = self.wumpus_consistent_with(&self.wumpus_clues);
+        if !candidates.is_empty() {
+            return candidates;
+        }
+        self.wumpus_clues = vec![room];
+        self.wumpus_consistent_with(&self.wumpus_clues)
+    }
+
+    fn wumpus_consistent_with(&self, rooms: &[usize]) -> Vec<usize> {
+        (0..self.exits.len())
+            .filter(|candidate| !rooms.contains(candidate))
+            .filter(|candidate| {
+                rooms.iter().all(|&room| {
+                    let near = self.exits[room]
+                        .as_ref()
+                        .is_some_and(|exits| exits.contains(candidate));
+                    self.clues[room].is_some_and(|clues| clues.stench == near)
+                })
+            })
+            .collect()
+    }
+
+    // No bats: next to a room where no squeak was heard since they last
+    // moved
+    fn is_bat_free(&self, room: usize) -> bool {
+        self.bat_clues.iter().any(|&known| {
+            known == room
+                || self.clues[known].is_some_and(|clues| !clues.squeak)
+                    && self.exits[known]
+                        .as_ref()
+                        .is_some_and(|exits| exits.contains(&room))
+        })
+    }
+
+    /// Picks the next action for a player in `room` with `arrows` left.
+    fn decide(&mut self, room: usize, arrows: u32) -> Decision {
+        let wumpus = self.wumpus_candidates(room);
+        // Logic only holds until a wandering wumpus moves again
+        let provable = !self.wandering;
+
+        if arrows > 0
+            && let [target] = wumpus[..]
+            && let Some(path) = self.arrow_path(room, target)
+        {
+            return Decision {
+                action: Action::Shoot(path),
+                certain: provable,
+            };
+        }
+
+        // Unexplored rooms within reach, nearest first, with the first step
+        // towards each of them
+        let frontier = self.frontier(room, &wumpus);
+        let risk = |target: usize| self.risk(target, &wumpus);
+
+        // The safe rooms without bats first, then the other safe ones
+        let safe = frontier
+            .iter()
+            .filter(|&&(target, _)| risk(target) == 0.0)
+            .min_by_key(|&&(target, _)| !self.is_bat_free(target));
+        if let Some(&(_, step)) = safe {
+            return Decision {
+                action: Action::Move(step),
+                certain: provable,
+            };
+        }
+
+        // No safe room: a shot with an even chance or better beats a leap
+        // into the dark, and so does any shot when there is nowhere to go
+        let shot = wumpus
+            .iter()
+            .find_map(|&target| self.arrow_path(room, target));
+        if arrows > 0
+            && let Some(path) = shot
+            && (wumpus.len() <= 2 || frontier.is_empty())
+        {
+            return Decision {
+                action: Action::Shoot(path),
+                certain: false,
+            };
+        }
+
+        let gamble = frontier
+            .iter()
+            .min_by(|&&(a, _), &&(b, _)| risk(a).total_cmp(&risk(b)));
+        let step = match gamble {
+            Some(&(_, step)) => step,
+            None => self.exits[room]
+                .as_ref()
+                .and_then(|exits| exits.first().copied())
+                .unwrap_or(room),
+        };
+        Decision {
+            action: Action::Move(step),
+            certain: false,
+        }
+    }
+
+    // Chance of dying on entering `room`, given where the wumpus can be
+    fn risk(&self, room: usize, wumpus: &[usize]) -> f64 {
+        let wumpus_chance = if wumpus.contains(&room) {
+            1.0 / wumpus.len() as f64
+        } else {
+            0.0
+        };
+        1.0 - (1.0 - self.pit_chance(room)) * (1.0 - wumpus_chance)
+    }
+
+    // Unexplored rooms reachable through visited rooms the wumpus cannot be
+    // in, nearest first, each with the first step of the way there
+    fn frontier(&self, room: usize, wumpus: &[usize]) -> Vec<(usize, usize)> {
+        let mut first_step = vec![None; self.exits.len()];
+        let mut frontier = Vec::new();
+        let mut queue = VecDeque::from([room]);
+        while let Some(current) = queue.pop_front() {
+            let Some(exits) = &self.exits[current] else {
+                continue;
+            };
+            for &next in exits {
+                if next == room || first_step[next].is_some() {
+                    continue;
+                }
+                let step = first_step[current].unwrap_or(next);
+                first_step[next] = Some(step);
+                if !self.is_visited(next) {
+                    frontier.push((next, step));
+                } else if !wumpus.contains(&next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        frontier
+    }
+
+    // Shortest arrow path from `room` to `target` through visited rooms,
+    // whose tunnels are known, if one is short enough
+    fn arrow_path(&self, room: usize, target: usize) -> Option<Vec<usize>> {
+        let mut previous = vec![None; self.exits.len()];
+        let mut queue = VecDeque::from([room]);
+        while let Some(current) = queue.pop_front() {
+            for &next in self.exits[current].as_deref().unwrap_or_default() {
+                if next == room || previous[next].is_some() {
+                    continue;
+                }
+                previous[next] = Some(current);
+                if next == target {
+                    let mut path = vec![target];
+                    while let Some(before) = previous[*path.last()?].filter(|&r| r != room) {
+                        path.push(before);
+                    }
+                    path.reverse();
+                    return (path.len() <= MAX_ARROW_PATH).then_some(path);
+                }
+                if self.is_visited(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Agent for Solver {
+    fn act(&mut self, percept: &Percept) -> Action {
+        self.observe(percept);
+        let action = if percept.bump {
+            // A refused action would be refused again: step aside instead,
+            // into the least risky of the other exits
+            let wumpus = self.wumpus_candidates(percept.room);
+            let refused = self.last.take();
+            let exits = percept
+                .exits
+                .iter()
+                .copied()
+                .filter(|&exit| refused != Some(Action::Move(exit)));
+            let exit =
+                exits.min_by(|&a, &b| self.risk(a, &wumpus).total_cmp(&self.risk(b, &wumpus)));
+            Action::Move(exit.unwrap_or(percept.exits[0]))
+        } else {
+            self.decide(percept.room, percept.arrows).action
+        };
+        self.last = Some(action.clone());
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::events::GameEvent;
+    use std::sync::Arc;
+
+    // Whether what happened after `decision` proves its claim wrong: a safe
+    // move walked into a pit or the wumpus, or a sure shot missed
+    fn broken_claim(decision: &Decision, events: &[GameEvent]) -> bool {
+        if !decision.certain {
+            return false;
+        }
+        match decision.action {
+            Action::Move(step) => events.iter().any(|event| {
+                matches!(
+                    *event,
+                    GameEvent::FellInPit { room } | GameEvent::EatenByWumpus { room } if room == step
+                )
+            }),
+            Action::Shoot(_) => !events
+                .iter()
+                .any(|event| matches!(event, GameEvent::WumpusKilled { .. })),
+        }
+    }
+
+    fn game(difficulty: Difficulty, seed: u64) -> GameModel {
+        let mut model = GameModel::with_seed_and_clock(seed, Arc::new(ManualClock::new(0.0)));
+        model.set_difficulty(difficulty);
+        model
+    }
+
+    #[test]
+    fn certain_decisions_hold() {
+        let (mut safe_moves, mut sure_shots) = (0, 0);
+        for difficulty in [Difficulty::Easy, Difficulty::Classic] {
+            for seed in 0..300 {
+                let mut model = game(difficulty, seed);
+                let mut solver = Solver::new(difficulty);
+                while !model.game_over {
+                    let percept = Percept::observe(&model, false);
+                    solver.observe(&percept);
+                    let decision = solver.decide(percept.room, percept.arrows);
+                    solver.last = Some(decision.action.clone());
+                    match decision.action {
+                        Action::Move(_) if decision.certain => safe_moves += 1,
+                        Action::Shoot(_) if decision.certain => sure_shots += 1,
+                        _ => {}
+                    }
+                    let events = model.apply(decision.action.clone()).unwrap();
+                    assert!(
+                        !broken_claim(&decision, &events),
+                        "{:?}, seed {}: {:?} led to {:?}",
+                        difficulty,
+                        seed,
+                        decision,
+                        events
+                    );
+                }
+            }
+        }
+        // The claims were put to the test, not just avoided
+        assert!(safe_moves > 0 && sure_shots > 0);
+    }
+
+    #[test]
+    fn certain_hints_hold() {
+        for difficulty in [Difficulty::Easy, Difficulty::Classic] {
+            for seed in 0..300 {
+                let mut model = game(difficulty, seed);
+                while let Some(decision) = Solver::hint(&model) {
+                    let events = model.apply(decision.action.clone()).unwrap();
+                    assert!(
+                        !broken_claim(&decision, &events),
+                        "{:?}, seed {}: {:?} led to {:?}",
+                        difficulty,
+                        seed,
+                        decision,
+                        events
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nothing_is_certain_where_the_wumpus_wanders() {
+        for difficulty in [Difficulty::Hard, Difficulty::Nightmare] {
+            for seed in 0..50 {
+                let mut model = game(difficulty, seed);
+                while let Some(decision) = Solver::hint(&model) {
+                    assert!(!decision.certain);
+                    model.apply(decision.action).unwrap();
+                }
+            }
+        }
+    }
+}
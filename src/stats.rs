@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Message;
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Outcome {
+    Won,
+    EatenByWumpus,
+    FellInPit,
+    /// The player's own crooked arrow came back to them.
+    ShotSelf,
+    OutOfArrows,
+}
+
+impl Outcome {
+    /// Message key of a short description of the outcome.
+    pub fn key(self) -> &'static str {
+        match self {
+            Outcome::Won => "outcome.won",
+            Outcome::EatenByWumpus => "outcome.eaten_by_wumpus",
+            Outcome::FellInPit => "outcome.fell_in_pit",
+            Outcome::ShotSelf => "outcome.shot_self",
+            Outcome::OutOfArrows => "outcome.out_of_arrows",
+        }
+    }
+}
+
+/// What the player did during one game.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GameStats {
+    /// Moves through tunnels, not counting the rides on bats.
+    pub moves: u32,
+    pub shots: u32,
+    pub bat_rides: u32,
+    pub rooms_explored: u32,
+    /// Seconds played, frozen once the game is over.
+    pub time: f64,
+}
+
+impl GameStats {
+    /// Summary of the counters, for the end of the game.
+    pub fn message(&self) -> Message {
+        Message::new("over.stats")
+            .arg("moves", self.moves)
+            .arg("shots", self.shots)
+            .arg("rides", self.bat_rides)
+            .arg("rooms", self.rooms_explored)
+    }
+}
@@ -0,0 +1,420 @@
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+
+/// Number of rooms of every built-in cave, as in the original game.
+pub const DEFAULT_NUM_ROOMS: usize = 20;
+
+// Maximum number of tunnels per room in randomly generated caves
+const RANDOM_MAX_DEGREE: usize = 3;
+
+/// The cave layouts that can be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CaveKind {
+    Dodecahedron,
+    MobiusStrip,
+    StringOfBeads,
+    HexNetwork,
+    Dendrite,
+    OneWayLattice,
+    Random,
+}
+
+impl CaveKind {
+    pub const ALL: [CaveKind; 7] = [
+        CaveKind::Dodecahedron,
+        CaveKind::MobiusStrip,
+        CaveKind::StringOfBeads,
+        CaveKind::HexNetwork,
+        CaveKind::Dendrite,
+        CaveKind::OneWayLattice,
+        CaveKind::Random,
+    ];
+
+    /// Message key of the cave's name.
+    pub fn key(self) -> &'static str {
+        match self {
+            CaveKind::Dodecahedron => "cave.dodecahedron",
+            CaveKind::MobiusStrip => "cave.mobius_strip",
+            CaveKind::StringOfBeads => "cave.string_of_beads",
+            CaveKind::HexNetwork => "cave.hex_network",
+            CaveKind::Dendrite => "cave.dendrite",
+            CaveKind::OneWayLattice => "cave.one_way_lattice",
+            CaveKind::Random => "cave.random",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = CaveKind::ALL.iter().position(|&k| k == self).unwrap();
+        CaveKind::ALL[(i + 1) % CaveKind::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let i = CaveKind::ALL.iter().position(|&k| k == self).unwrap();
+        CaveKind::ALL[(i + CaveKind::ALL.len() - 1) % CaveKind::ALL.len()]
+    }
+}
+
+/// A cave as a graph: every room lists the rooms its tunnels lead to.
+///
+/// Rooms may have any number of exits, and in directed caves (the one-way
+/// lattice) a tunnel from `a` to `b` does not imply one from `b` to `a`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaveTopology {
+    kind: CaveKind,
+    tunnels: Vec<Vec<usize>>,
+    directed: bool,
+}
+
+impl CaveTopology {
+    /// Builds a cave of the given kind. Only `CaveKind::Random` draws from
+    /// `rng`; every other kind is fixed.
+    pub fn generate<R: Rng + ?Sized>(kind: CaveKind, rng: &mut R) -> Self {
+        match kind {
+            CaveKind::Dodecahedron => CaveTopology::dodecahedron(),
+            CaveKind::MobiusStrip => CaveTopology::mobius_strip(),
+            CaveKind::StringOfBeads => CaveTopology::string_of_beads(),
+            CaveKind::HexNetwork => CaveTopology::hex_network(),
+            CaveKind::Dendrite => CaveTopology::dendrite(),
+            CaveKind::OneWayLattice => CaveTopology::one_way_lattice(),
+            CaveKind::Random => CaveTopology::random(DEFAULT_NUM_ROOMS, rng),
+        }
+    }
+
+    /// The classic cave: the vertices of a dodecahedron, three tunnels each.
+    pub fn dodecahedron() -> Self {
+        let tunnels = [
+            [1, 4, 5],    // 0
+            [0, 2, 7],    // 1
+            [1, 3, 9],    // 2
+            [2, 4, 11],   // 3
+            [0, 3, 13],   // 4
+            [6, 14, 0],   // 5
+            [5, 7, 18],   // 6
+            [6, 8, 1],    // 7
+            [7, 9, 19],   // 8
+            [8, 10, 2],   // 9
+            [9, 11, 15],  // 10
+            [10, 12, 3],  // 11
+            [11, 13, 16], // 12
+            [12, 14, 4],  // 13
+            [5, 13, 17],  // 14
+            [16, 19, 10], // 15
+            [15, 17, 12], // 16
+            [16, 18, 14], // 17
+            [17, 19, 6],  // 18
+            [15, 18, 8],  // 19
+        ];
+        CaveTopology {
+            kind: CaveKind::Dodecahedron,
+            tunnels: tunnels.iter().map(|t| t.to_vec()).collect(),
+            directed: false,
+        }
+    }
+
+    /// A ladder of ten rungs whose two rails are joined with a half twist.
+    pub fn mobius_strip() -> Self {
+        let half = DEFAULT_NUM_ROOMS / 2;
+        let mut edges = Vec::new();
+        for i in 0..half {
+            edges.push((i, i + half));
+            if i + 1 < half {
+                edges.push((i, i + 1));
+                edges.push((i + half, i + half + 1));
+            }
+        }
+        // The twist: each rail continues on the other one
+        edges.push((half - 1, half));
+        edges.push((DEFAULT_NUM_ROOMS - 1, 0));
+        CaveTopology::from_edges(CaveKind::MobiusStrip, DEFAULT_NUM_ROOMS, &edges)
+    }
+
+    /// Five diamond-shaped beads of four rooms threaded on a loop.
+    pub fn string_of_beads() -> Self {
+        let beads = DEFAULT_NUM_ROOMS / 4;
+        let mut edges = Vec::new();
+        for b in 0..beads {
+            let first = 4 * b;
+            edges.push((first, first + 1));
+            edges.push((first, first + 2));
+            edges.push((first + 1, first + 3));
+            edges.push((first + 2, first + 3));
+            edges.push((first + 3, 4 * ((b + 1) % beads)));
+        }
+        CaveTopology::from_edges(CaveKind::StringOfBeads, DEFAULT_NUM_ROOMS, &edges)
+    }
+
+    /// Hexagonal rooms tiling a torus of 4 rows by 5 columns: six tunnels
+    /// per room.
+    pub fn hex_network() -> Self {
+        let (rows, cols) = (4, 5);
+        let room = |r: usize, c: usize| (r % rows) * cols + c % cols;
+        let mut edges = Vec::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                edges.push((room(r, c), room(r, c + 1)));
+                edges.push((room(r, c), room(r + 1, c)));
+                edges.push((room(r, c), room(r + 1, c + cols - 1)));
+            }
+        }
+        CaveTopology::from_edges(CaveKind::HexNetwork, rows * cols, &edges)
+    }
+
+    /// A branching tree full of dead ends: room `i` hangs below `(i - 1) / 2`.
+    pub fn dendrite() -> Self {
+        let edges: Vec<_> = (1..DEFAULT_NUM_ROOMS).map(|i| ((i - 1) / 2, i)).collect();
+        CaveTopology::from_edges(CaveKind::Dendrite, DEFAULT_NUM_ROOMS, &edges)
+    }
+
+    /// A 4 by 5 torus where tunnels can only be travelled right, down or
+    /// diagonally up-left.
+    pub fn one_way_lattice() -> Self {
+        let (rows, cols) = (4, 5);
+        let room = |r: usize, c: usize| (r % rows) * cols + c % cols;
+        let tunnels = (0..rows * cols)
+            .map(|i| {
+                let (r, c) = (i / cols, i % cols);
+                vec![
+                    room(r, c + 1),
+                    room(r + 1, c),
+                    room(r + rows - 1, c + cols - 1),
+                ]
+            })
+            .collect();
+        CaveTopology {
+            kind: CaveKind::OneWayLattice,
+            tunnels,
+            directed: true,
+        }
+    }
+
+    /// A random connected cave where every room has one to three tunnels.
+    pub fn random<R: Rng + ?Sized>(num_rooms: usize, rng: &mut R) -> Self {
+        let mut order: Vec<usize> = (0..num_rooms).collect();
+        order.shuffle(rng);
+
+        let mut tunnels = vec![Vec::new(); num_rooms];
+
+        // A random spanning tree keeps every room reachable
+        for i in 1..order.len() {
+            let candidates: Vec<usize> = order[..i]
+                .iter()
+                .copied()
+                .filter(|&r| tunnels[r].len() < RANDOM_MAX_DEGREE)
+                .collect();
+            let parent = candidates[rng.random_range(0..candidates.len())];
+            tunnels[parent].push(order[i]);
+            tunnels[order[i]].push(parent);
+        }
+
+        // Extra tunnels create loops until no room can take any more
+        let mut open: Vec<usize> = (0..num_rooms)
+            .filter(|&r| tunnels[r].len() < RANDOM_MAX_DEGREE)
+            .collect();
+        open.shuffle(rng);
+        for i in 0..open.len() {
+            for j in i + 1..open.len() {
+                let (a, b) = (open[i], open[j]);
+                if tunnels[a].len() < RANDOM_MAX_DEGREE
+                    && tunnels[b].len() < RANDOM_MAX_DEGREE
+                    && !tunnels[a].contains(&b)
+                {
+                    tunnels[a].push(b);
+                    tunnels[b].push(a);
+                }
+            }
+        }
+
+        CaveTopology {
+            kind: CaveKind::Random,
+            tunnels,
+            directed: false,
+        }
+    }
+
+    fn from_edges(kind: CaveKind, num_rooms: usize, edges: &[(usize, usize)]) -> Self {
+        let mut tunnels = vec![Vec::new(); num_rooms];
+        for &(a, b) in edges {
+            if a != b && !tunnels[a].contains(&b) {
+                tunnels[a].push(b);
+                tunnels[b].push(a);
+            }
+        }
+        CaveTopology {
+            kind,
+            tunnels,
+            directed: false,
+        }
+    }
+
+    /// Whether the cave can be played: every tunnel leads to an existing
+    /// room, every room has a way out, undirected tunnels can be walked both
+    /// ways, and every room can be reached from every other.
+    pub fn is_valid(&self) -> bool {
+        let n = self.tunnels.len();
+        n > 0
+            && self
+                .tunnels
+                .iter()
+                .all(|exits| !exits.is_empty() && exits.iter().all(|&room| room < n))
+            && (self.directed || self.is_symmetric())
+            && self.is_connected()
+    }
+
+    // Every tunnel has its way back
+    fn is_symmetric(&self) -> bool {
+        self.tunnels
+            .iter()
+            .enumerate()
+            .all(|(from, exits)| exits.iter().all(|&to| self.tunnels[to].contains(&from)))
+    }
+
+    // Every room is reached from room 0 and, when tunnels are one-way,
+    // reaches it back
+    fn is_connected(&self) -> bool {
+        if !reaches_all(&self.tunnels) {
+            return false;
+        }
+        if !self.directed {
+            return true;
+        }
+        let mut reversed = vec![Vec::new(); self.tunnels.len()];
+        for (from, to) in self.edges() {
+            reversed[to].push(from);
+        }
+        reaches_all(&reversed)
+    }
+
+    pub fn kind(&self) -> CaveKind {
+        self.kind
+    }
+
+    pub fn num_rooms(&self) -> usize {
+        self.tunnels.len()
+    }
+
+    /// Whether tunnels can only be travelled in the direction they are listed.
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Rooms reachable through a single tunnel from `room`.
+    pub fn exits(&self, room: usize) -> &[usize] {
+        &self.tunnels[room]
+    }
+
+    pub fn max_exits(&self) -> usize {
+        self.tunnels.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    pub fn is_tunnel(&self, from: usize, to: usize) -> bool {
+        self.tunnels[from].contains(&to)
+    }
+
+    /// Every tunnel once, as `(from, to)`. Undirected tunnels are listed with
+    /// `from < to`.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        for (from, exits) in self.tunnels.iter().enumerate() {
+            for &to in exits {
+                if self.directed || from < to {
+                    edges.push((from, to));
+                }
+            }
+        }
+        edges
+    }
+}
+
+// Whether every room can be reached from room 0
+fn reaches_all(tunnels: &[Vec<usize>]) -> bool {
+    let mut seen = vec![false; tunnels.len()];
+    let mut stack = vec![0];
+    seen[0] = true;
+    while let Some(room) = stack.pop() {
+        for &next in &tunnels[room] {
+            if !seen[next] {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    seen.into_iter().all(|seen| seen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // Smallest and largest number of exits of the rooms of `kind`
+    fn expected_exits(kind: CaveKind) -> (usize, usize) {
+        match kind {
+            CaveKind::Dodecahedron | CaveKind::MobiusStrip | CaveKind::OneWayLattice => (3, 3),
+            CaveKind::StringOfBeads => (2, 3),
+            CaveKind::HexNetwork => (6, 6),
+            CaveKind::Dendrite | CaveKind::Random => (1, 3),
+        }
+    }
+
+    fn check(cave: &CaveTopology, kind: CaveKind) {
+        assert_eq!(cave.kind(), kind);
+        assert_eq!(cave.num_rooms(), DEFAULT_NUM_ROOMS, "{:?}", kind);
+        assert!(cave.is_valid(), "{:?}", kind);
+        assert_eq!(cave.is_directed(), kind == CaveKind::OneWayLattice);
+        assert!(cave.is_directed() || cave.is_symmetric(), "{:?}", kind);
+
+        let (min, max) = expected_exits(kind);
+        for room in 0..cave.num_rooms() {
+            let exits = cave.exits(room);
+            assert!(
+                (min..=max).contains(&exits.len()),
+                "{:?} room {}",
+                kind,
+                room
+            );
+            assert!(!exits.contains(&room), "{:?} room {}", kind, room);
+            for (i, exit) in exits.iter().enumerate() {
+                assert!(!exits[..i].contains(exit), "{:?} room {}", kind, room);
+            }
+        }
+    }
+
+    #[test]
+    fn every_kind_of_cave_is_playable() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for kind in CaveKind::ALL {
+            check(&CaveTopology::generate(kind, &mut rng), kind);
+        }
+    }
+
+    #[test]
+    fn random_caves_are_playable() {
+        for seed in 0..200 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            check(
+                &CaveTopology::random(DEFAULT_NUM_ROOMS, &mut rng),
+                CaveKind::Random,
+            );
+        }
+    }
+
+    #[test]
+    fn broken_caves_are_invalid() {
+        let cave = |tunnels: Vec<Vec<usize>>, directed| CaveTopology {
+            kind: CaveKind::Random,
+            tunnels,
+            directed,
+        };
+        assert!(!cave(vec![], false).is_valid());
+        assert!(!cave(vec![vec![1], vec![]], false).is_valid());
+        assert!(!cave(vec![vec![1], vec![2]], false).is_valid());
+        // Two rooms joined in one direction only
+        assert!(!cave(vec![vec![1], vec![0, 2], vec![1, 0]], false).is_valid());
+        // Two separate pairs of rooms
+        assert!(!cave(vec![vec![1], vec![0], vec![3], vec![2]], false).is_valid());
+        // One way in, no way back out
+        assert!(!cave(vec![vec![1], vec![2], vec![1]], true).is_valid());
+        assert!(cave(vec![vec![1], vec![2], vec![0]], true).is_valid());
+    }
+}